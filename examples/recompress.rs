@@ -1,7 +1,5 @@
 use clap::{App, Arg};
 use flate2::read::MultiGzDecoder;
-use flate2::write::GzEncoder;
-use flate2::Compression;
 use std::boxed::Box;
 use std::fs::File;
 use std::io::{BufReader, Read};
@@ -10,7 +8,7 @@ pub fn main() -> Result<(), vcf::VCFError> {
     let matches = App::new("Recompress VCF")
         .version("0.1")
         .author("Yasunobu Okamura")
-        .about("Parse and recompress VCF file")
+        .about("Parse and recompress VCF file with BGZF")
         .arg(
             Arg::with_name("input")
                 .index(1)
@@ -26,6 +24,19 @@ pub fn main() -> Result<(), vcf::VCFError> {
                 .required(true)
                 .help("Output VCF"),
         )
+        .arg(
+            Arg::with_name("index")
+                .long("index")
+                .short("i")
+                .help("Write tabix index to <output>.tbi. Input must be sorted."),
+        )
+        .arg(
+            Arg::with_name("csi")
+                .long("csi")
+                .short("c")
+                .conflicts_with("index")
+                .help("Write CSI index to <output>.csi. Input must be sorted."),
+        )
         .get_matches();
     let input_vcf_path = matches.value_of("input").unwrap();
     let output_vcf_path = matches.value_of("output").unwrap();
@@ -40,11 +51,31 @@ pub fn main() -> Result<(), vcf::VCFError> {
     let mut vcf_reader = vcf::VCFReader::new(reader)?;
     let mut record = vcf_reader.empty_record();
 
-    let writer = GzEncoder::new(File::create(output_vcf_path)?, Compression::default());
-    let mut vcf_writer = vcf::VCFWriter::new(writer, &vcf_reader.header())?;
-
-    while vcf_reader.next_record(&mut record)? {
-        vcf_writer.write_record(&record)?;
+    if matches.is_present("index") || matches.is_present("csi") {
+        let output = File::create(output_vcf_path)?;
+        let (mut vcf_writer, suffix) = if matches.is_present("csi") {
+            (
+                vcf::IndexedVCFWriter::new_csi(output, vcf_reader.header(), 14, 5)?,
+                "csi",
+            )
+        } else {
+            (
+                vcf::IndexedVCFWriter::new(output, vcf_reader.header())?,
+                "tbi",
+            )
+        };
+        while vcf_reader.next_record(&mut record)? {
+            vcf_writer.write_record(&record)?;
+        }
+        let (_, index) = vcf_writer.finish()?;
+        index.write(File::create(format!("{}.{}", output_vcf_path, suffix))?)?;
+    } else {
+        let writer = vcf::BGZFWriter::new(File::create(output_vcf_path)?);
+        let mut vcf_writer = vcf::VCFWriter::new(writer, vcf_reader.header())?;
+        while vcf_reader.next_record(&mut record)? {
+            vcf_writer.write_record(&record)?;
+        }
+        vcf_writer.finish()?;
     }
 
     Ok(())
@@ -0,0 +1,86 @@
+use crate::{header, parse_line, write_header, VCFError, VCFHeader, VCFRecord};
+use tokio::io::{AsyncBufRead, AsyncBufReadExt, AsyncWrite, AsyncWriteExt};
+
+/// VCF reader over `tokio::io::AsyncBufRead`.
+pub struct AsyncVCFReader<R: AsyncBufRead + Unpin> {
+    buffer: Vec<u8>,
+    current_line: u64,
+    reader: R,
+    vcf_header: VCFHeader,
+}
+
+impl<R: AsyncBufRead + Unpin> AsyncVCFReader<R> {
+    pub async fn new(mut reader: R) -> Result<Self, VCFError> {
+        // Collect header lines and parse them with the same parser as VCFReader
+        let mut header_data = Vec::new();
+        loop {
+            if !reader.fill_buf().await?.starts_with(b"#") {
+                break;
+            }
+            let line_start = header_data.len();
+            reader.read_until(b'\n', &mut header_data).await?;
+            if !header_data[line_start..].starts_with(b"##") {
+                break;
+            }
+        }
+        let (current_line, vcf_header) = header::parse_header(&mut &header_data[..])?;
+        Ok(AsyncVCFReader {
+            buffer: Vec::new(),
+            current_line,
+            reader,
+            vcf_header,
+        })
+    }
+
+    /// Read next record.
+    /// Return false if no record was remained.
+    pub async fn next_record(&mut self, record: &mut VCFRecord) -> Result<bool, VCFError> {
+        self.buffer.clear();
+        self.reader.read_until(b'\n', &mut self.buffer).await?;
+        self.current_line += 1;
+        if self.buffer.is_empty() {
+            return Ok(false);
+        }
+        parse_line(record, &self.buffer, Some(self.current_line), None, false)?;
+        Ok(true)
+    }
+
+    pub fn header(&self) -> &VCFHeader {
+        &self.vcf_header
+    }
+
+    pub fn empty_record(&self) -> VCFRecord {
+        VCFRecord::new(self.vcf_header.clone())
+    }
+}
+
+/// VCF writer over `tokio::io::AsyncWrite`.
+pub struct AsyncVCFWriter<W: AsyncWrite + Unpin> {
+    buffer: Vec<u8>,
+    writer: W,
+}
+
+impl<W: AsyncWrite + Unpin> AsyncVCFWriter<W> {
+    pub async fn new(mut writer: W, header: &VCFHeader) -> Result<Self, VCFError> {
+        let mut buffer = Vec::new();
+        write_header(&mut buffer, header)?;
+        writer.write_all(&buffer).await?;
+        Ok(AsyncVCFWriter { buffer, writer })
+    }
+
+    pub async fn write_record(&mut self, vcf_record: &VCFRecord) -> Result<(), VCFError> {
+        self.buffer.clear();
+        vcf_record.write_record(&mut self.buffer)?;
+        self.writer.write_all(&self.buffer).await?;
+        Ok(())
+    }
+
+    /// Flush and return the inner writer.
+    pub async fn finish(mut self) -> Result<W, VCFError> {
+        self.writer.flush().await?;
+        Ok(self.writer)
+    }
+}
+
+#[cfg(test)]
+mod test;
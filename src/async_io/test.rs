@@ -0,0 +1,70 @@
+use super::*;
+use crate::VCFReader;
+use std::io::BufReader;
+
+#[tokio::test]
+async fn test_async_reader_writer() -> Result<(), VCFError> {
+    let data = &include_bytes!("../../testfiles/1kGP-subset.vcf")[..];
+    let mut vcf_reader = VCFReader::new(BufReader::new(data))?;
+    let expected = vcf_reader.records().collect::<Result<Vec<_>, _>>()?;
+
+    let mut reader = AsyncVCFReader::new(tokio::io::BufReader::new(data)).await?;
+    assert_eq!(reader.header().items(), vcf_reader.header().items());
+    assert_eq!(reader.header().samples(), vcf_reader.header().samples());
+
+    let mut writer = AsyncVCFWriter::new(Vec::new(), reader.header()).await?;
+    let mut record = reader.empty_record();
+    let mut count = 0;
+    while reader.next_record(&mut record).await? {
+        let mut written = Vec::new();
+        record.write_record(&mut written)?;
+        let mut expected_line = Vec::new();
+        expected[count].write_record(&mut expected_line)?;
+        assert_eq!(written, expected_line);
+        writer.write_record(&record).await?;
+        count += 1;
+    }
+    assert_eq!(count, expected.len());
+
+    let written = writer.finish().await?;
+    let mut sync_written = Vec::new();
+    let mut sync_writer = crate::VCFWriter::new(&mut sync_written, vcf_reader.header())?;
+    for one in expected.iter() {
+        sync_writer.write_record(one)?;
+    }
+    assert_eq!(written, sync_written);
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_async_reader_error() -> Result<(), VCFError> {
+    let data = &b"##fileformat=VCFv4.2
+#CHROM\tPOS\tID\tREF\tALT\tQUAL\tFILTER\tINFO
+1\t1\t.\tA\tC\t.\t.\t.
+1\tX
+"[..];
+    let mut reader = AsyncVCFReader::new(data).await?;
+    let mut record = reader.empty_record();
+    assert!(reader.next_record(&mut record).await?);
+    assert_eq!(record.position, 1);
+    assert!(matches!(
+        reader.next_record(&mut record).await,
+        Err(VCFError::RecordParseError(x)) if x.line == Some(4)
+    ));
+
+    // Header without #CHROM line
+    let data = &b"##fileformat=VCFv4.2
+1\t1\t.\tA\tC\t.\t.\t.
+"[..];
+    let mut reader = AsyncVCFReader::new(data).await?;
+    let mut record = reader.empty_record();
+    assert!(reader.next_record(&mut record).await?);
+    assert_eq!(record.reference, b"A");
+    assert!(!reader.next_record(&mut record).await?);
+
+    assert!(matches!(
+        AsyncVCFReader::new(&b"##fileformat\n#CHROM\n"[..]).await,
+        Err(VCFError::HeaderParseError(_))
+    ));
+    Ok(())
+}
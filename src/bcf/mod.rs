@@ -0,0 +1,429 @@
+use crate::bgzf::read_exact_or_eof;
+use crate::{U8Vec, VCFError, VCFHeader, VCFHeaderContent, VCFRecord};
+use std::collections::HashMap;
+use std::convert::TryFrom;
+use std::io::{Read, Write};
+
+mod writer;
+
+pub use writer::BCFWriter;
+
+const BCF_TYPE_NULL: u8 = 0;
+const BCF_TYPE_INT8: u8 = 1;
+const BCF_TYPE_INT16: u8 = 2;
+const BCF_TYPE_INT32: u8 = 3;
+const BCF_TYPE_FLOAT: u8 = 5;
+const BCF_TYPE_CHAR: u8 = 7;
+
+const BCF_FLOAT_MISSING: u32 = 0x7f80_0001;
+const BCF_FLOAT_END_OF_VECTOR: u32 = 0x7f80_0002;
+
+/// Size of fixed fields in shared data, from CHROM to n_fmt_sample.
+const BCF_FIXED_SIZE: usize = 24;
+
+/// Upper limit of `l_shared + l_indiv` to avoid huge allocations for broken files.
+const BCF_MAX_RECORD_SIZE: u64 = 1 << 30;
+
+/// Upper limit of `l_text`, the length of the header text.
+const BCF_MAX_HEADER_SIZE: u32 = 1 << 30;
+
+/// String and contig dictionaries of BCF.
+/// Indexes are given by `IDX` field or the order of header lines.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub(crate) struct Dictionary {
+    pub strings: Vec<U8Vec>,
+    pub string_to_index: HashMap<U8Vec, usize>,
+    pub contigs: Vec<U8Vec>,
+    pub contig_to_index: HashMap<U8Vec, usize>,
+}
+
+impl Dictionary {
+    pub fn new(header: &VCFHeader) -> Self {
+        let mut dictionary = Dictionary::default();
+        add_entry(
+            &mut dictionary.strings,
+            &mut dictionary.string_to_index,
+            b"PASS",
+            Some(0),
+        );
+        for one in header.items() {
+            match one.contents() {
+                VCFHeaderContent::FILTER { id, .. }
+                | VCFHeaderContent::INFO { id, .. }
+                | VCFHeaderContent::FORMAT { id, .. } => add_entry(
+                    &mut dictionary.strings,
+                    &mut dictionary.string_to_index,
+                    id,
+                    one.idx(),
+                ),
+                VCFHeaderContent::Contig { id, .. } => add_entry(
+                    &mut dictionary.contigs,
+                    &mut dictionary.contig_to_index,
+                    id,
+                    one.idx(),
+                ),
+                _ => {}
+            }
+        }
+        dictionary
+    }
+
+    pub fn string(&self, index: i32) -> Result<&[u8], VCFError> {
+        lookup(&self.strings, index).ok_or(VCFError::BCFParseError("Unknown string index"))
+    }
+
+    pub fn contig(&self, index: i32) -> Result<&[u8], VCFError> {
+        lookup(&self.contigs, index).ok_or(VCFError::BCFParseError("Unknown contig index"))
+    }
+}
+
+fn add_entry(
+    list: &mut Vec<U8Vec>,
+    map: &mut HashMap<U8Vec, usize>,
+    id: &[u8],
+    idx: Option<usize>,
+) {
+    if map.contains_key(id) {
+        return;
+    }
+    let index = idx.unwrap_or(list.len());
+    if list.len() <= index {
+        list.resize(index + 1, Vec::new());
+    }
+    list[index] = id.to_vec();
+    map.insert(id.to_vec(), index);
+}
+
+fn lookup(list: &[U8Vec], index: i32) -> Option<&[u8]> {
+    usize::try_from(index)
+        .ok()
+        .and_then(|x| list.get(x))
+        .filter(|x| !x.is_empty())
+        .map(|x| &x[..])
+}
+
+/// One element of typed value.
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Value {
+    Integer(i32),
+    Float(f32),
+    Missing,
+    EndOfVector,
+}
+
+/// Decoder of typed values in BCF record.
+struct TypedValueReader<'a> {
+    data: &'a [u8],
+}
+
+impl<'a> TypedValueReader<'a> {
+    fn take(&mut self, length: usize) -> Result<&'a [u8], VCFError> {
+        if self.data.len() < length {
+            return Err(VCFError::BCFParseError("Unexpected end of record"));
+        }
+        let (data, rest) = self.data.split_at(length);
+        self.data = rest;
+        Ok(data)
+    }
+
+    fn read_i32(&mut self) -> Result<i32, VCFError> {
+        let data = self.take(4)?;
+        Ok(i32::from_le_bytes([data[0], data[1], data[2], data[3]]))
+    }
+
+    /// Read type descriptor and return the type and the number of elements.
+    fn read_type(&mut self) -> Result<(u8, usize), VCFError> {
+        let descriptor = self.take(1)?[0];
+        let value_type = descriptor & 0x0f;
+        let mut length = usize::from(descriptor >> 4);
+        if length == 15 {
+            length = usize::try_from(self.read_int()?)
+                .map_err(|_| VCFError::BCFParseError("Negative length"))?;
+        }
+        Ok((value_type, length))
+    }
+
+    /// Read one typed integer, such as a dictionary key.
+    fn read_int(&mut self) -> Result<i32, VCFError> {
+        let (value_type, length) = self.read_type()?;
+        if length != 1 {
+            return Err(VCFError::BCFParseError("Integer is expected"));
+        }
+        match self.read_value(value_type)? {
+            Value::Integer(x) => Ok(x),
+            _ => Err(VCFError::BCFParseError("Integer is expected")),
+        }
+    }
+
+    fn read_value(&mut self, value_type: u8) -> Result<Value, VCFError> {
+        Ok(match value_type {
+            BCF_TYPE_INT8 => match self.take(1)?[0] as i8 {
+                i8::MIN => Value::Missing,
+                -127 => Value::EndOfVector,
+                x => Value::Integer(i32::from(x)),
+            },
+            BCF_TYPE_INT16 => {
+                let data = self.take(2)?;
+                match i16::from_le_bytes([data[0], data[1]]) {
+                    i16::MIN => Value::Missing,
+                    -32767 => Value::EndOfVector,
+                    x => Value::Integer(i32::from(x)),
+                }
+            }
+            BCF_TYPE_INT32 => match self.read_i32()? {
+                i32::MIN => Value::Missing,
+                -2_147_483_647 => Value::EndOfVector,
+                x => Value::Integer(x),
+            },
+            BCF_TYPE_FLOAT => match self.read_i32()? as u32 {
+                BCF_FLOAT_MISSING => Value::Missing,
+                BCF_FLOAT_END_OF_VECTOR => Value::EndOfVector,
+                x => Value::Float(f32::from_bits(x)),
+            },
+            _ => return Err(VCFError::BCFParseError("Unknown value type")),
+        })
+    }
+
+    /// Read typed string. Trailing NUL paddings are removed.
+    fn read_string(&mut self) -> Result<&'a [u8], VCFError> {
+        let (value_type, length) = self.read_type()?;
+        match value_type {
+            BCF_TYPE_NULL => Ok(&[]),
+            BCF_TYPE_CHAR => Ok(trim_nul(self.take(length)?)),
+            _ => Err(VCFError::BCFParseError("String is expected")),
+        }
+    }
+
+    /// Read `length` elements and store them as text, same as VCF.
+    fn read_values(
+        &mut self,
+        value_type: u8,
+        length: usize,
+        result: &mut Vec<U8Vec>,
+    ) -> Result<(), VCFError> {
+        result.clear();
+        match value_type {
+            BCF_TYPE_NULL => {}
+            BCF_TYPE_CHAR => {
+                let data = trim_nul(self.take(length)?);
+                if !data.is_empty() {
+                    result.extend(data.split(|x| *x == b',').map(|x| x.to_vec()));
+                }
+            }
+            _ => {
+                for _ in 0..length {
+                    match self.read_value(value_type)? {
+                        Value::Integer(x) => result.push(x.to_string().into_bytes()),
+                        Value::Float(x) => result.push(x.to_string().into_bytes()),
+                        Value::Missing => result.push(b".".to_vec()),
+                        Value::EndOfVector => {}
+                    }
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Read `length` encoded alleles and store them as a genotype text such as `0/1`.
+    fn read_genotype(
+        &mut self,
+        value_type: u8,
+        length: usize,
+        result: &mut Vec<U8Vec>,
+    ) -> Result<(), VCFError> {
+        result.clear();
+        let mut genotype = Vec::new();
+        for i in 0..length {
+            let allele = match self.read_value(value_type)? {
+                Value::Integer(x) => x,
+                Value::Missing => 0,
+                Value::EndOfVector => continue,
+                Value::Float(_) => return Err(VCFError::BCFParseError("Invalid genotype")),
+            };
+            if i != 0 {
+                genotype.push(if allele & 1 == 1 { b'|' } else { b'/' });
+            }
+            if allele >> 1 == 0 {
+                genotype.push(b'.');
+            } else {
+                write!(genotype, "{}", (allele >> 1) - 1)?;
+            }
+        }
+        if !genotype.is_empty() {
+            result.push(genotype);
+        }
+        Ok(())
+    }
+}
+
+fn trim_nul(data: &[u8]) -> &[u8] {
+    match data.iter().position(|x| *x == 0) {
+        Some(x) => &data[..x],
+        None => data,
+    }
+}
+
+/// BCF reader.
+///
+/// `reader` should provide uncompressed BCF. Wrap it with `BGZFReader` to read
+/// compressed BCF.
+pub struct BCFReader<R: Read> {
+    reader: R,
+    buffer: Vec<u8>,
+    vcf_header: VCFHeader,
+    dictionary: Dictionary,
+}
+
+impl<R: Read> BCFReader<R> {
+    pub fn new(mut reader: R) -> Result<Self, VCFError> {
+        let mut magic = [0u8; 5];
+        reader.read_exact(&mut magic)?;
+        if &magic[..4] != b"BCF\x02" {
+            return Err(VCFError::BCFParseError("Not a BCF version 2 file"));
+        }
+        let mut length = [0u8; 4];
+        reader.read_exact(&mut length)?;
+        let length = u32::from_le_bytes(length);
+        if length > BCF_MAX_HEADER_SIZE {
+            return Err(VCFError::BCFParseError("Too long header"));
+        }
+        let mut text = vec![0u8; length as usize];
+        reader.read_exact(&mut text)?;
+        let (_, vcf_header) = crate::header::parse_header(&mut trim_nul(&text))?;
+
+        Ok(BCFReader {
+            reader,
+            buffer: Vec::new(),
+            dictionary: Dictionary::new(&vcf_header),
+            vcf_header,
+        })
+    }
+
+    /// Read next record.
+    /// Return false if no record was remained.
+    pub fn next_record(&mut self, record: &mut VCFRecord) -> Result<bool, VCFError> {
+        let mut lengths = [0u8; 8];
+        if !read_exact_or_eof(&mut self.reader, &mut lengths)? {
+            return Ok(false);
+        }
+        let shared_length = u32::from_le_bytes([lengths[0], lengths[1], lengths[2], lengths[3]]);
+        let individual_length =
+            u32::from_le_bytes([lengths[4], lengths[5], lengths[6], lengths[7]]);
+        let record_size = u64::from(shared_length) + u64::from(individual_length);
+        if (shared_length as usize) < BCF_FIXED_SIZE {
+            return Err(VCFError::BCFParseError("Too short record"));
+        }
+        if record_size > BCF_MAX_RECORD_SIZE {
+            return Err(VCFError::BCFParseError("Too long record"));
+        }
+        self.buffer.clear();
+        self.buffer.resize(record_size as usize, 0);
+        self.reader.read_exact(&mut self.buffer)?;
+
+        let dictionary = &self.dictionary;
+        let mut data = TypedValueReader { data: &self.buffer };
+        let chromosome = data.read_i32()?;
+        record.chromosome.clear();
+        record
+            .chromosome
+            .extend_from_slice(dictionary.contig(chromosome)?);
+        let position = data.read_i32()?;
+        record.position =
+            u64::try_from(position).map_err(|_| VCFError::BCFParseError("Negative position"))? + 1;
+        let _reference_length = data.read_i32()?;
+        record.qual = match data.read_value(BCF_TYPE_FLOAT)? {
+            // Convert via text to keep the value written in VCF, such as 495.23
+            Value::Float(x) => x.to_string().parse().ok(),
+            _ => None,
+        };
+        let allele_info = data.read_i32()? as u32;
+        let format_sample = data.read_i32()? as u32;
+        let allele_count = (allele_info >> 16) as usize;
+        let info_count = (allele_info & 0xffff) as usize;
+        let format_count = (format_sample >> 24) as usize;
+        let sample_count = (format_sample & 0xff_ffff) as usize;
+        if sample_count != self.vcf_header.samples().len() {
+            return Err(VCFError::BCFParseError("Wrong number of samples"));
+        }
+
+        let id = data.read_string()?;
+        record.id.clear();
+        if !id.is_empty() && id != b"." {
+            record
+                .id
+                .extend(id.split(|x| *x == b',').map(|x| x.to_vec()));
+        }
+
+        record.reference.clear();
+        record.alternative.clear();
+        for i in 0..allele_count {
+            let allele = data.read_string()?;
+            if i == 0 {
+                record.reference.extend_from_slice(allele);
+            } else {
+                record.alternative.push(allele.to_vec());
+            }
+        }
+
+        let (value_type, length) = data.read_type()?;
+        record.filter.clear();
+        for _ in 0..length {
+            if let Value::Integer(x) = data.read_value(value_type)? {
+                record.filter.push(dictionary.string(x)?.to_vec());
+            }
+        }
+
+        record.info.resize(info_count, (Vec::new(), Vec::new()));
+        for one in record.info.iter_mut() {
+            let key = data.read_int()?;
+            one.0.clear();
+            one.0.extend_from_slice(dictionary.string(key)?);
+            let (value_type, length) = data.read_type()?;
+            data.read_values(value_type, length, &mut one.1)?;
+        }
+
+        let mut data = TypedValueReader {
+            data: &self.buffer[shared_length as usize..],
+        };
+        record.format.resize(format_count, Vec::new());
+        record.lazy_genotype = None;
+        record.genotype.resize(sample_count, Vec::new());
+        for one in record.genotype.iter_mut() {
+            one.resize(format_count, Vec::new());
+        }
+        for i in 0..format_count {
+            let key = dictionary.string(data.read_int()?)?;
+            record.format[i].clear();
+            record.format[i].extend_from_slice(key);
+            let (value_type, length) = data.read_type()?;
+            for one in record.genotype.iter_mut() {
+                let values = &mut one[i];
+                if key == b"GT" && value_type != BCF_TYPE_CHAR {
+                    data.read_genotype(value_type, length, values)?;
+                } else {
+                    data.read_values(value_type, length, values)?;
+                }
+                if values.is_empty() {
+                    values.push(b".".to_vec());
+                }
+            }
+        }
+        if format_count == 0 {
+            record.genotype.clear();
+        }
+
+        record.recreate_info_and_genotype_index();
+        Ok(true)
+    }
+
+    pub fn header(&self) -> &VCFHeader {
+        &self.vcf_header
+    }
+
+    pub fn empty_record(&self) -> VCFRecord {
+        VCFRecord::new(self.vcf_header.clone())
+    }
+}
+
+#[cfg(test)]
+mod test;
@@ -0,0 +1,267 @@
+use super::*;
+use crate::{BGZFReader, VCFReader};
+use std::fs::File;
+use std::io::BufReader;
+
+#[test]
+fn test_dictionary() -> Result<(), VCFError> {
+    let header = VCFReader::new(BufReader::new(
+        &b"##fileformat=VCFv4.2
+##FILTER=<ID=q10,Description=\"Quality below 10\">
+##INFO=<ID=DP,Number=1,Type=Integer,Description=\"Depth\">
+##FORMAT=<ID=DP,Number=1,Type=Integer,Description=\"Depth\">
+##FORMAT=<ID=GT,Number=1,Type=String,Description=\"Genotype\",IDX=5>
+##contig=<ID=chr2,IDX=1>
+##contig=<ID=chr1,IDX=0>
+#CHROM\tPOS\tID\tREF\tALT\tQUAL\tFILTER\tINFO
+"[..],
+    ))?
+    .header()
+    .clone();
+    let dictionary = Dictionary::new(&header);
+    assert_eq!(dictionary.string(0)?, b"PASS");
+    assert_eq!(dictionary.string(1)?, b"q10");
+    assert_eq!(dictionary.string(2)?, b"DP");
+    assert_eq!(dictionary.string(5)?, b"GT");
+    assert!(dictionary.string(3).is_err());
+    assert!(dictionary.string(-1).is_err());
+    assert_eq!(dictionary.contig(0)?, b"chr1");
+    assert_eq!(dictionary.contig(1)?, b"chr2");
+    Ok(())
+}
+
+#[test]
+fn test_typed_values() -> Result<(), VCFError> {
+    let mut result = Vec::new();
+    let mut data = TypedValueReader {
+        data: &[
+            0x31, 0x01, 0x80, 0x81, // int8 x3
+            0x22, 0x00, 0x01, 0x00, 0x80, // int16 x2
+            0x15, 0x00, 0x00, 0x20, 0x3f, // float x1
+            0x57, b'a', b',', b'b', 0x00, 0x00, // char x5
+            0xf1, 0x11, 0x10, // int8 x16 without values
+        ][..],
+    };
+    let (value_type, length) = data.read_type()?;
+    data.read_values(value_type, length, &mut result)?;
+    assert_eq!(result, vec![b"1".to_vec(), b".".to_vec()]);
+    let (value_type, length) = data.read_type()?;
+    data.read_values(value_type, length, &mut result)?;
+    assert_eq!(result, vec![b"256".to_vec(), b".".to_vec()]);
+    let (value_type, length) = data.read_type()?;
+    data.read_values(value_type, length, &mut result)?;
+    assert_eq!(result, vec![b"0.625".to_vec()]);
+    let (value_type, length) = data.read_type()?;
+    data.read_values(value_type, length, &mut result)?;
+    assert_eq!(result, vec![b"a".to_vec(), b"b".to_vec()]);
+    assert_eq!(data.read_type()?, (BCF_TYPE_INT8, 16));
+    assert!(data.read_value(BCF_TYPE_INT8).is_err());
+
+    let mut data = TypedValueReader {
+        data: &[0x04, 0x06, 0x81, 0x04, 0x01][..],
+    };
+    data.read_genotype(BCF_TYPE_INT8, 2, &mut result)?;
+    assert_eq!(result, vec![b"1/2".to_vec()]);
+    data.read_genotype(BCF_TYPE_INT8, 1, &mut result)?;
+    assert!(result.is_empty());
+    data.read_genotype(BCF_TYPE_INT8, 2, &mut result)?;
+    assert_eq!(result, vec![b"1|.".to_vec()]);
+    Ok(())
+}
+
+#[test]
+fn test_bcf_reader() -> Result<(), VCFError> {
+    let mut bcf_reader =
+        BCFReader::new(BGZFReader::new(File::open("./testfiles/1kGP-subset.bcf")?))?;
+    let mut vcf_reader =
+        VCFReader::new(BufReader::new(File::open("./testfiles/1kGP-subset.vcf")?))?;
+    assert_eq!(bcf_reader.header().samples(), vcf_reader.header().samples());
+    assert_eq!(
+        bcf_reader.header().items().len(),
+        vcf_reader.header().items().len()
+    );
+
+    let mut bcf_record = bcf_reader.empty_record();
+    let mut vcf_record = vcf_reader.empty_record();
+    let mut count = 0;
+    while vcf_reader.next_record(&mut vcf_record)? {
+        assert!(bcf_reader.next_record(&mut bcf_record)?);
+        let mut bcf_line = Vec::new();
+        let mut vcf_line = Vec::new();
+        bcf_record.write_record(&mut bcf_line)?;
+        vcf_record.write_record(&mut vcf_line)?;
+        assert_eq!(
+            String::from_utf8(bcf_line).unwrap(),
+            String::from_utf8(vcf_line).unwrap()
+        );
+        assert_eq!(
+            bcf_record.genotype(b"SRP150637__HG00100", b"GT"),
+            vcf_record.genotype(b"SRP150637__HG00100", b"GT")
+        );
+        count += 1;
+    }
+    assert!(!bcf_reader.next_record(&mut bcf_record)?);
+    assert_eq!(count, 306);
+    Ok(())
+}
+
+#[test]
+fn test_bcf_reader_not_bcf() {
+    assert!(BCFReader::new(BGZFReader::new(
+        File::open("./testfiles/1kGP-subset.vcf.gz").unwrap()
+    ))
+    .is_err());
+}
+
+#[test]
+fn test_bcf_reader_too_long_header() {
+    let mut data = b"BCF\x02\x02".to_vec();
+    data.extend_from_slice(&u32::MAX.to_le_bytes());
+    match BCFReader::new(&data[..]) {
+        Err(VCFError::BCFParseError(x)) => assert_eq!(x, "Too long header"),
+        _ => panic!(),
+    }
+}
+
+#[test]
+fn test_bcf_reader_too_long_record() -> Result<(), VCFError> {
+    let header = VCFReader::new(BufReader::new(File::open("./testfiles/simple1.vcf")?))?
+        .header()
+        .clone();
+    let mut data = Vec::new();
+    BCFWriter::new(&mut data, &header)?;
+    data.extend_from_slice(&u32::MAX.to_le_bytes());
+    data.extend_from_slice(&u32::MAX.to_le_bytes());
+
+    let mut bcf_reader = BCFReader::new(&data[..])?;
+    let mut record = bcf_reader.empty_record();
+    match bcf_reader.next_record(&mut record) {
+        Err(VCFError::BCFParseError(x)) => assert_eq!(x, "Too long record"),
+        x => panic!("{:?}", x),
+    }
+    Ok(())
+}
+
+#[test]
+fn test_bcf_writer() -> Result<(), VCFError> {
+    let mut vcf_reader =
+        VCFReader::new(BufReader::new(File::open("./testfiles/1kGP-subset.vcf")?))?;
+    let mut data = Vec::new();
+    let mut bcf_writer = BCFWriter::new(&mut data, vcf_reader.header())?;
+    let mut vcf_record = vcf_reader.empty_record();
+    while vcf_reader.next_record(&mut vcf_record)? {
+        bcf_writer.write_record(&vcf_record)?;
+    }
+
+    // Same as the file written by htslib
+    let mut expected = Vec::new();
+    BGZFReader::new(File::open("./testfiles/1kGP-subset.bcf")?).read_to_end(&mut expected)?;
+    assert_eq!(data, expected);
+    Ok(())
+}
+
+#[test]
+fn test_bcf_writer_round_trip() -> Result<(), VCFError> {
+    let vcf_data = &b"##fileformat=VCFv4.2
+##FILTER=<ID=q10,Description=\"Quality below 10\">
+##INFO=<ID=DB,Number=0,Type=Flag,Description=\"dbSNP membership\">
+##INFO=<ID=AF,Number=A,Type=Float,Description=\"Allele Frequency\">
+##INFO=<ID=LEN,Number=.,Type=Integer,Description=\"Length\">
+##INFO=<ID=GENE,Number=.,Type=String,Description=\"Gene name\">
+##INFO=<ID=END,Number=1,Type=Integer,Description=\"End position\">
+##FORMAT=<ID=GT,Number=1,Type=String,Description=\"Genotype\">
+##FORMAT=<ID=DP,Number=1,Type=Integer,Description=\"Depth\">
+##FORMAT=<ID=FT,Number=1,Type=String,Description=\"Filter\">
+##contig=<ID=chr1>
+##contig=<ID=chr2>
+#CHROM\tPOS\tID\tREF\tALT\tQUAL\tFILTER\tINFO\tFORMAT\tS1\tS2
+chr1\t100\trs1,rs2\tA\tC,T\t10.5\tPASS\tDB;AF=0.25,.;LEN=1,300,-70000;GENE=ABC,DEFGH\tGT:DP:FT\t0|1:3:PASS\t1/2:100000:q10
+chr1\t200\t.\tAT\tA\t.\tq10\t.\tGT:DP\t1:.\t./.:5
+chr2\t1\t.\tG\t.\t.\t.\tEND=10\tGT:DP\t0\t.
+"[..];
+    let mut vcf_reader = VCFReader::new(BufReader::new(vcf_data))?;
+    let mut bcf_writer = BCFWriter::new(crate::BGZFWriter::new(Vec::new()), vcf_reader.header())?;
+    let mut vcf_record = vcf_reader.empty_record();
+    while vcf_reader.next_record(&mut vcf_record)? {
+        bcf_writer.write_record(&vcf_record)?;
+    }
+    let data = bcf_writer.finish()?;
+
+    let mut bcf_reader = BCFReader::new(BGZFReader::new(&data[..]))?;
+    assert_eq!(bcf_reader.header().samples(), vcf_reader.header().samples());
+    assert_eq!(bcf_reader.header().filter(b"PASS").unwrap().id, b"PASS");
+    let mut bcf_record = bcf_reader.empty_record();
+    for expected in [
+        &b"chr1\t100\trs1,rs2\tA\tC,T\t10.5\tPASS\tDB;AF=0.25,.;LEN=1,300,-70000;GENE=ABC,DEFGH\tGT:DP:FT\t0|1:3:PASS\t1/2:100000:q10\n"[..],
+        &b"chr1\t200\t.\tAT\tA\t.\tq10\t.\tGT:DP\t1:.\t./.:5\n"[..],
+        &b"chr2\t1\t.\tG\t.\t.\t.\tEND=10\tGT:DP\t0:.\t.:.\n"[..],
+    ]
+    .iter()
+    {
+        assert!(bcf_reader.next_record(&mut bcf_record)?);
+        let mut line = Vec::new();
+        bcf_record.write_record(&mut line)?;
+        assert_eq!(
+            String::from_utf8(line).unwrap(),
+            String::from_utf8(expected.to_vec()).unwrap()
+        );
+    }
+    assert!(!bcf_reader.next_record(&mut bcf_record)?);
+    Ok(())
+}
+
+#[test]
+fn test_bcf_writer_end_before_position() -> Result<(), VCFError> {
+    let vcf_data = &b"##fileformat=VCFv4.2
+##INFO=<ID=END,Number=1,Type=Integer,Description=\"End position\">
+##contig=<ID=chr1>
+#CHROM\tPOS\tID\tREF\tALT\tQUAL\tFILTER\tINFO
+"[..];
+    let header = VCFReader::new(BufReader::new(vcf_data))?.header().clone();
+    let mut header_only = Vec::new();
+    BCFWriter::new(&mut header_only, &header)?;
+
+    for (line, expected) in [
+        (&b"chr1\t100\t.\tAT\tA\t.\t.\tEND=50\n"[..], 1),
+        (&b"chr1\t100\t.\tA\t<DEL>\t.\t.\tEND=109\n"[..], 10),
+    ]
+    .iter()
+    {
+        let mut data = Vec::new();
+        let mut bcf_writer = BCFWriter::new(&mut data, &header)?;
+        bcf_writer.write_record(&VCFRecord::from_bytes(line, 1, header.clone())?)?;
+        drop(bcf_writer);
+        // rlen follows l_shared, l_indiv, CHROM and POS
+        let offset = header_only.len() + 16;
+        let mut rlen = [0u8; 4];
+        rlen.copy_from_slice(&data[offset..offset + 4]);
+        assert_eq!(i32::from_le_bytes(rlen), *expected);
+    }
+    Ok(())
+}
+
+#[test]
+fn test_bcf_writer_undefined() -> Result<(), VCFError> {
+    let header = VCFReader::new(BufReader::new(File::open("./testfiles/simple1.vcf")?))?
+        .header()
+        .clone();
+    let mut bcf_writer = BCFWriter::new(Vec::new(), &header)?;
+    for (line, ok) in [
+        (&b"13\t100\t.\tA\tC\t.\t.\tAC=1\n"[..], true),
+        (&b"1\t100\t.\tA\tC\t.\t.\t.\n"[..], false),
+        (&b"13\t100\t.\tA\tC\t.\tq10\t.\n"[..], false),
+        (&b"13\t100\t.\tA\tC\t.\t.\tXX=1\n"[..], false),
+        (&b"13\t100\t.\tA\tC\t.\t.\tAC=X\n"[..], false),
+        (
+            &b"13\t100\t.\tA\tC\t.\t.\t.\tGT:XX\t0/1:1\t0/1:1\n"[..],
+            false,
+        ),
+        (&b"13\t100\t.\tA\tC\t.\t.\t.\tGT\t0/X\t0/1\n"[..], false),
+    ]
+    .iter()
+    {
+        let record = VCFRecord::from_bytes(line, 1, header.clone())?;
+        assert_eq!(bcf_writer.write_record(&record).is_ok(), *ok);
+    }
+    Ok(())
+}
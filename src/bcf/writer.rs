@@ -0,0 +1,454 @@
+use super::{
+    Dictionary, BCF_FLOAT_END_OF_VECTOR, BCF_FLOAT_MISSING, BCF_TYPE_CHAR, BCF_TYPE_FLOAT,
+    BCF_TYPE_INT16, BCF_TYPE_INT32, BCF_TYPE_INT8, BCF_TYPE_NULL,
+};
+use crate::bgzf::{BGZFWriter, VirtualOffset};
+use crate::{U8Vec, VCFError, VCFHeader, VCFHeaderContent, VCFRecord, ValueType};
+use std::convert::TryFrom;
+use std::io::Write;
+use std::str;
+
+const INT_MISSING: i32 = i32::MIN;
+const INT_END_OF_VECTOR: i32 = i32::MIN + 1;
+
+/// Smallest values which can be stored in each integer type. Smaller values are reserved.
+const BCF_MIN_INT8: i32 = -120;
+const BCF_MIN_INT16: i32 = -32760;
+
+const PASS_LINE: &[u8] = b"##FILTER=<ID=PASS,Description=\"All filters passed\",IDX=0>\n";
+
+/// BCF writer.
+///
+/// Records are written as uncompressed BCF. Wrap `writer` with `BGZFWriter`
+/// to write compressed BCF.
+pub struct BCFWriter<W: Write> {
+    writer: W,
+    vcf_header: VCFHeader,
+    dictionary: Dictionary,
+    shared: Vec<u8>,
+    individual: Vec<u8>,
+}
+
+impl<W: Write> BCFWriter<W> {
+    pub fn new(mut writer: W, header: &VCFHeader) -> Result<Self, VCFError> {
+        let dictionary = Dictionary::new(header);
+        let text = header_text(header, &dictionary);
+        writer.write_all(b"BCF\x02\x02")?;
+        writer.write_all(&(text.len() as u32).to_le_bytes())?;
+        writer.write_all(&text)?;
+
+        Ok(BCFWriter {
+            writer,
+            vcf_header: header.clone(),
+            dictionary,
+            shared: Vec::new(),
+            individual: Vec::new(),
+        })
+    }
+
+    pub fn write_record(&mut self, vcf_record: &VCFRecord) -> Result<(), VCFError> {
+        self.shared.clear();
+        self.individual.clear();
+        self.encode_shared(vcf_record)?;
+        self.encode_individual(vcf_record)?;
+
+        self.writer
+            .write_all(&(self.shared.len() as u32).to_le_bytes())?;
+        self.writer
+            .write_all(&(self.individual.len() as u32).to_le_bytes())?;
+        self.writer.write_all(&self.shared)?;
+        self.writer.write_all(&self.individual)?;
+        Ok(())
+    }
+
+    pub fn header(&self) -> &VCFHeader {
+        &self.vcf_header
+    }
+
+    fn encode_shared(&mut self, vcf_record: &VCFRecord) -> Result<(), VCFError> {
+        let sample_count = self.vcf_header.samples().len();
+        if !vcf_record.format.is_empty() && vcf_record.sample_genotype_count() != sample_count {
+            return Err(VCFError::BCFEncodeError("Wrong number of samples"));
+        }
+        if vcf_record.info.len() > 0xffff
+            || vcf_record.alternative.len() >= 0xffff
+            || vcf_record.format.len() > 0xff
+            || sample_count > 0xff_ffff
+        {
+            return Err(VCFError::BCFEncodeError("Too many fields"));
+        }
+
+        let shared = &mut self.shared;
+        let chromosome = self
+            .dictionary
+            .contig_to_index
+            .get(&vcf_record.chromosome)
+            .ok_or(VCFError::BCFEncodeError("Contig is not defined in header"))?;
+        let position = i32::try_from(vcf_record.position.saturating_sub(1))
+            .map_err(|_| VCFError::BCFEncodeError("Too large position"))?;
+        // END before POS is malformed, but common in real files
+        let end = crate::index::record_end(vcf_record).max(vcf_record.position);
+        let reference_length = i32::try_from(end + 1 - vcf_record.position)
+            .map_err(|_| VCFError::BCFEncodeError("Too long reference"))?;
+        shared.extend_from_slice(&(*chromosome as i32).to_le_bytes());
+        shared.extend_from_slice(&position.to_le_bytes());
+        shared.extend_from_slice(&reference_length.to_le_bytes());
+        let qual = match vcf_record.qual {
+            Some(x) => (x as f32).to_bits(),
+            None => BCF_FLOAT_MISSING,
+        };
+        shared.extend_from_slice(&qual.to_le_bytes());
+        let allele_count = if vcf_record.reference.is_empty() {
+            0
+        } else {
+            1 + vcf_record.alternative.len()
+        };
+        let allele_info = (allele_count << 16) | vcf_record.info.len();
+        shared.extend_from_slice(&(allele_info as u32).to_le_bytes());
+        let format_sample = (vcf_record.format.len() << 24) | sample_count;
+        shared.extend_from_slice(&(format_sample as u32).to_le_bytes());
+
+        write_string(shared, &join(&vcf_record.id, b','));
+        if allele_count > 0 {
+            write_string(shared, &vcf_record.reference);
+            for one in vcf_record.alternative.iter() {
+                write_string(shared, one);
+            }
+        }
+
+        let mut filter = Vec::with_capacity(vcf_record.filter.len());
+        for one in vcf_record.filter.iter() {
+            let index = self
+                .dictionary
+                .string_to_index
+                .get(one)
+                .ok_or(VCFError::BCFEncodeError("FILTER is not defined in header"))?;
+            filter.push(*index as i32);
+        }
+        write_integers(shared, &filter, filter.len());
+
+        for (key, values) in vcf_record.info.iter() {
+            let index = self
+                .dictionary
+                .string_to_index
+                .get(key)
+                .ok_or(VCFError::BCFEncodeError("INFO is not defined in header"))?;
+            write_int(shared, *index as i32);
+            let value_type = self
+                .vcf_header
+                .info(key)
+                .map(|x| x.value_type)
+                .unwrap_or(&ValueType::String);
+            match value_type {
+                ValueType::Flag => write_type(shared, BCF_TYPE_NULL, 0),
+                ValueType::Integer => {
+                    let values = values
+                        .iter()
+                        .map(|x| parse_integer(x))
+                        .collect::<Result<Vec<_>, _>>()?;
+                    write_integers(shared, &values, values.len());
+                }
+                ValueType::Float => {
+                    let values = values
+                        .iter()
+                        .map(|x| parse_float(x))
+                        .collect::<Result<Vec<_>, _>>()?;
+                    write_floats(shared, &values, values.len());
+                }
+                _ => write_string(shared, &join(values, b',')),
+            }
+        }
+
+        Ok(())
+    }
+
+    fn encode_individual(&mut self, vcf_record: &VCFRecord) -> Result<(), VCFError> {
+        let individual = &mut self.individual;
+        let sample_count = vcf_record.sample_genotype_count();
+        for (i, key) in vcf_record.format.iter().enumerate() {
+            let index = self
+                .dictionary
+                .string_to_index
+                .get(key)
+                .ok_or(VCFError::BCFEncodeError("FORMAT is not defined in header"))?;
+            write_int(individual, *index as i32);
+            let sample_values = (0..sample_count).map(|x| {
+                vcf_record
+                    .sample_genotype(x)
+                    .and_then(|y| y.get(i))
+                    .map(|y| &y[..])
+                    .unwrap_or(&[])
+            });
+
+            let value_type = if key == b"GT" {
+                // Genotypes are encoded as integers
+                &ValueType::Integer
+            } else {
+                self.vcf_header
+                    .format(key)
+                    .map(|x| x.value_type)
+                    .unwrap_or(&ValueType::String)
+            };
+            match value_type {
+                ValueType::Integer => {
+                    let (values, width) = if key == b"GT" {
+                        encode_samples(sample_values, 0, INT_END_OF_VECTOR, |x, y| {
+                            match x.first() {
+                                Some(genotype) => encode_genotype(genotype, y),
+                                None => Ok(()),
+                            }
+                        })?
+                    } else {
+                        encode_samples(sample_values, INT_MISSING, INT_END_OF_VECTOR, |x, y| {
+                            for one in x.iter() {
+                                y.push(parse_integer(one)?);
+                            }
+                            Ok(())
+                        })?
+                    };
+                    write_integers(individual, &values, width);
+                }
+                ValueType::Float => {
+                    let (values, width) = encode_samples(
+                        sample_values,
+                        BCF_FLOAT_MISSING,
+                        BCF_FLOAT_END_OF_VECTOR,
+                        |x, y| {
+                            for one in x.iter() {
+                                y.push(parse_float(one)?);
+                            }
+                            Ok(())
+                        },
+                    )?;
+                    write_floats(individual, &values, width);
+                }
+                _ => {
+                    let values: Vec<U8Vec> = sample_values.map(|x| join(x, b',')).collect();
+                    // Keep at least one NUL for each sample, same as htslib
+                    let width = values.iter().map(|x| x.len()).max().unwrap_or(0) + 1;
+                    write_type(individual, BCF_TYPE_CHAR, width);
+                    for one in values.iter() {
+                        individual.extend_from_slice(one);
+                        individual.resize(individual.len() + width - one.len(), 0);
+                    }
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+impl<W: Write> BCFWriter<BGZFWriter<W>> {
+    /// Virtual offset of the next record.
+    pub fn virtual_offset(&self) -> VirtualOffset {
+        self.writer.virtual_offset()
+    }
+
+    /// Write remaining records and BGZF EOF marker, then return the inner writer.
+    pub fn finish(self) -> Result<W, VCFError> {
+        Ok(self.writer.finish()?)
+    }
+}
+
+/// Header text with `IDX` fields, same as htslib writes.
+/// PASS filter is added after `fileformat` line if it is not defined.
+fn header_text(header: &VCFHeader, dictionary: &Dictionary) -> U8Vec {
+    let items = header.items();
+    let pass_position = match items.first().map(|x| x.contents()) {
+        _ if header.filter(b"PASS").is_some() => None,
+        Some(VCFHeaderContent::FileFormat(_)) => Some(1),
+        _ => Some(0),
+    };
+
+    let mut text = Vec::new();
+    for i in 0..=items.len() {
+        if pass_position == Some(i) {
+            text.extend_from_slice(PASS_LINE);
+        }
+        let one = match items.get(i) {
+            Some(x) => x,
+            None => break,
+        };
+        let line = one.line();
+        let line = line.strip_suffix(b"\n").unwrap_or(line);
+        let line = line.strip_suffix(b"\r").unwrap_or(line);
+        let index = match one.contents() {
+            VCFHeaderContent::FILTER { id, .. }
+            | VCFHeaderContent::INFO { id, .. }
+            | VCFHeaderContent::FORMAT { id, .. } => dictionary.string_to_index.get(id),
+            VCFHeaderContent::Contig { id, .. } => dictionary.contig_to_index.get(id),
+            _ => None,
+        };
+        match (index, line.strip_suffix(b">")) {
+            (Some(index), Some(body)) if one.idx().is_none() => {
+                text.extend_from_slice(body);
+                write!(text, ",IDX={}>", index).unwrap();
+            }
+            _ => text.extend_from_slice(line),
+        }
+        text.push(b'\n');
+    }
+
+    text.extend_from_slice(b"#CHROM\tPOS\tID\tREF\tALT\tQUAL\tFILTER\tINFO");
+    if !header.samples().is_empty() {
+        text.extend_from_slice(b"\tFORMAT");
+        for one in header.samples() {
+            text.push(b'\t');
+            text.extend_from_slice(one);
+        }
+    }
+    text.extend_from_slice(b"\n\0");
+    text
+}
+
+fn join(values: &[U8Vec], separator: u8) -> U8Vec {
+    let mut result = Vec::new();
+    for (i, one) in values.iter().enumerate() {
+        if i != 0 {
+            result.push(separator);
+        }
+        result.extend_from_slice(one);
+    }
+    result
+}
+
+fn parse_integer(value: &[u8]) -> Result<i32, VCFError> {
+    if value == b"." {
+        return Ok(INT_MISSING);
+    }
+    str::from_utf8(value)
+        .ok()
+        .and_then(|x| x.parse::<i32>().ok())
+        .filter(|x| *x > INT_END_OF_VECTOR)
+        .ok_or(VCFError::BCFEncodeError("Invalid integer value"))
+}
+
+fn parse_float(value: &[u8]) -> Result<u32, VCFError> {
+    if value == b"." {
+        return Ok(BCF_FLOAT_MISSING);
+    }
+    str::from_utf8(value)
+        .ok()
+        .and_then(|x| x.parse::<f32>().ok())
+        .map(|x| x.to_bits())
+        .ok_or(VCFError::BCFEncodeError("Invalid float value"))
+}
+
+/// Encode genotype text such as `0/1` or `1|.`.
+fn encode_genotype(genotype: &[u8], result: &mut Vec<i32>) -> Result<(), VCFError> {
+    let mut phased = false;
+    let mut rest = genotype;
+    loop {
+        let end = rest
+            .iter()
+            .position(|x| *x == b'/' || *x == b'|')
+            .unwrap_or(rest.len());
+        let allele = match &rest[..end] {
+            b"." => 0,
+            x => {
+                str::from_utf8(x)
+                    .ok()
+                    .and_then(|x| x.parse::<i32>().ok())
+                    .filter(|x| (0..(i32::MAX >> 1) - 1).contains(x))
+                    .ok_or(VCFError::BCFEncodeError("Invalid genotype"))?
+                    + 1
+            }
+        };
+        result.push((allele << 1) | i32::from(phased));
+        if end == rest.len() {
+            return Ok(());
+        }
+        phased = rest[end] == b'|';
+        rest = &rest[end + 1..];
+    }
+}
+
+/// Encode values of each sample and pad them to the same width with end-of-vector.
+/// Samples without values are stored as missing.
+fn encode_samples<'a, T: Copy>(
+    samples: impl Iterator<Item = &'a [U8Vec]>,
+    missing: T,
+    end_of_vector: T,
+    encode: impl Fn(&[U8Vec], &mut Vec<T>) -> Result<(), VCFError>,
+) -> Result<(Vec<T>, usize), VCFError> {
+    let mut values = Vec::new();
+    let mut width = 1;
+    for one in samples {
+        let mut sample = Vec::new();
+        encode(one, &mut sample)?;
+        if sample.is_empty() {
+            sample.push(missing);
+        }
+        width = width.max(sample.len());
+        values.push(sample);
+    }
+
+    let mut result = Vec::with_capacity(values.len() * width);
+    for one in values {
+        result.extend_from_slice(&one);
+        result.resize(result.len() + width - one.len(), end_of_vector);
+    }
+    Ok((result, width))
+}
+
+fn write_type(data: &mut Vec<u8>, value_type: u8, length: usize) {
+    if length < 15 {
+        data.push(((length as u8) << 4) | value_type);
+    } else {
+        data.push(0xf0 | value_type);
+        write_int(data, length as i32);
+    }
+}
+
+/// Write one typed integer with the smallest type.
+fn write_int(data: &mut Vec<u8>, value: i32) {
+    write_integers(data, &[value], 1);
+}
+
+/// Write integers with the smallest type which can store all values.
+/// `length` is the number of values in one vector.
+fn write_integers(data: &mut Vec<u8>, values: &[i32], length: usize) {
+    let (min, max) = values
+        .iter()
+        .filter(|x| **x != INT_MISSING && **x != INT_END_OF_VECTOR)
+        .fold((0, 0), |(min, max), x| (min.min(*x), max.max(*x)));
+    if values.is_empty() {
+        write_type(data, BCF_TYPE_NULL, 0);
+    } else if min >= BCF_MIN_INT8 && max <= i32::from(i8::MAX) {
+        write_type(data, BCF_TYPE_INT8, length);
+        for one in values {
+            data.push(match *one {
+                INT_MISSING => i8::MIN,
+                INT_END_OF_VECTOR => i8::MIN + 1,
+                x => x as i8,
+            } as u8);
+        }
+    } else if min >= BCF_MIN_INT16 && max <= i32::from(i16::MAX) {
+        write_type(data, BCF_TYPE_INT16, length);
+        for one in values {
+            let value = match *one {
+                INT_MISSING => i16::MIN,
+                INT_END_OF_VECTOR => i16::MIN + 1,
+                x => x as i16,
+            };
+            data.extend_from_slice(&value.to_le_bytes());
+        }
+    } else {
+        write_type(data, BCF_TYPE_INT32, length);
+        for one in values {
+            data.extend_from_slice(&one.to_le_bytes());
+        }
+    }
+}
+
+fn write_floats(data: &mut Vec<u8>, values: &[u32], length: usize) {
+    write_type(data, BCF_TYPE_FLOAT, length);
+    for one in values {
+        data.extend_from_slice(&one.to_le_bytes());
+    }
+}
+
+fn write_string(data: &mut Vec<u8>, value: &[u8]) {
+    write_type(data, BCF_TYPE_CHAR, value.len());
+    data.extend_from_slice(value);
+}
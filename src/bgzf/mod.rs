@@ -0,0 +1,219 @@
+use flate2::{Crc, Decompress, FlushDecompress};
+use std::io::{self, BufRead, Read, Seek, SeekFrom};
+
+#[cfg(feature = "parallel")]
+mod parallel;
+mod writer;
+
+#[cfg(feature = "parallel")]
+pub use parallel::ParallelBGZFReader;
+pub use writer::BGZFWriter;
+
+const BGZF_FIXED_HEADER_SIZE: usize = 12;
+const BGZF_FOOTER_SIZE: usize = 8;
+
+/// A position in a BGZF file.
+///
+/// The upper 48 bits are the offset of a compressed block in the file and
+/// the lower 16 bits are the offset in the uncompressed block.
+#[derive(Debug, Clone, Copy, Default, Hash, PartialEq, Eq, PartialOrd, Ord)]
+pub struct VirtualOffset(pub u64);
+
+impl VirtualOffset {
+    pub fn new(block_address: u64, offset_in_block: u16) -> Self {
+        VirtualOffset((block_address << 16) | u64::from(offset_in_block))
+    }
+
+    pub fn block_address(self) -> u64 {
+        self.0 >> 16
+    }
+
+    pub fn offset_in_block(self) -> u16 {
+        (self.0 & 0xffff) as u16
+    }
+}
+
+/// BGZF block reader.
+pub struct BGZFReader<R: Read> {
+    reader: R,
+    block_address: u64,
+    next_block_address: u64,
+    compressed: Vec<u8>,
+    buffer: Vec<u8>,
+    position: usize,
+    decompress: Decompress,
+}
+
+impl<R: Read> BGZFReader<R> {
+    pub fn new(reader: R) -> Self {
+        BGZFReader {
+            reader,
+            block_address: 0,
+            next_block_address: 0,
+            compressed: Vec::new(),
+            buffer: Vec::new(),
+            position: 0,
+            decompress: Decompress::new(false),
+        }
+    }
+
+    /// Virtual offset of the next byte to be read.
+    pub fn virtual_offset(&self) -> VirtualOffset {
+        if self.position < self.buffer.len() {
+            VirtualOffset::new(self.block_address, self.position as u16)
+        } else {
+            VirtualOffset::new(self.next_block_address, 0)
+        }
+    }
+
+    /// Read and decompress next block.
+    /// Return false if no block was remained.
+    fn read_block(&mut self) -> io::Result<bool> {
+        let block_size = match read_raw_block(&mut self.reader, &mut self.compressed)? {
+            Some(x) => x,
+            None => return Ok(false),
+        };
+        decompress_block(&self.compressed, &mut self.buffer, &mut self.decompress)?;
+        self.block_address = self.next_block_address;
+        self.next_block_address += block_size as u64;
+        self.position = 0;
+        Ok(true)
+    }
+}
+
+/// Read next block and store compressed data and footer in `compressed`.
+/// Return the size of the block, or `None` if no block was remained.
+pub(crate) fn read_raw_block<R: Read>(
+    reader: &mut R,
+    compressed: &mut Vec<u8>,
+) -> io::Result<Option<usize>> {
+    let mut header = [0u8; BGZF_FIXED_HEADER_SIZE];
+    if !read_exact_or_eof(reader, &mut header)? {
+        return Ok(None);
+    }
+    if header[0] != 31 || header[1] != 139 || header[2] != 8 || header[3] & 4 == 0 {
+        return Err(invalid_data("Not a BGZF block"));
+    }
+    let extra_length = usize::from(u16::from_le_bytes([header[10], header[11]]));
+    compressed.resize(extra_length, 0);
+    reader.read_exact(compressed)?;
+    let block_size =
+        find_block_size(compressed).ok_or_else(|| invalid_data("No BC field in BGZF header"))?;
+    let remaining = block_size
+        .checked_sub(BGZF_FIXED_HEADER_SIZE + extra_length)
+        .filter(|x| *x >= BGZF_FOOTER_SIZE)
+        .ok_or_else(|| invalid_data("Invalid BGZF block size"))?;
+    compressed.resize(remaining, 0);
+    reader.read_exact(compressed)?;
+    Ok(Some(block_size))
+}
+
+/// Decompress data read by `read_raw_block` into `buffer` and check CRC.
+pub(crate) fn decompress_block(
+    compressed: &[u8],
+    buffer: &mut Vec<u8>,
+    decompress: &mut Decompress,
+) -> io::Result<()> {
+    let (data, footer) = compressed.split_at(compressed.len() - BGZF_FOOTER_SIZE);
+    let expected_crc = u32::from_le_bytes([footer[0], footer[1], footer[2], footer[3]]);
+    let uncompressed_size =
+        u32::from_le_bytes([footer[4], footer[5], footer[6], footer[7]]) as usize;
+
+    buffer.clear();
+    buffer.reserve(uncompressed_size);
+    decompress.reset(false);
+    decompress
+        .decompress_vec(data, buffer, FlushDecompress::Finish)
+        .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))?;
+    if buffer.len() != uncompressed_size {
+        return Err(invalid_data("Unexpected uncompressed BGZF block size"));
+    }
+    let mut crc = Crc::new();
+    crc.update(buffer);
+    if crc.sum() != expected_crc {
+        return Err(invalid_data("BGZF block CRC mismatch"));
+    }
+    Ok(())
+}
+
+impl<R: Read + Seek> BGZFReader<R> {
+    /// Move to the virtual offset.
+    pub fn seek(&mut self, offset: VirtualOffset) -> io::Result<()> {
+        let block_address = offset.block_address();
+        if block_address != self.block_address || self.block_address == self.next_block_address {
+            self.reader.seek(SeekFrom::Start(block_address))?;
+            self.block_address = block_address;
+            self.next_block_address = block_address;
+            self.buffer.clear();
+            self.position = 0;
+            self.read_block()?;
+        }
+        let offset_in_block = usize::from(offset.offset_in_block());
+        if offset_in_block > self.buffer.len() {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                "Virtual offset is out of BGZF block",
+            ));
+        }
+        self.position = offset_in_block;
+        Ok(())
+    }
+}
+
+impl<R: Read> Read for BGZFReader<R> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        let available = self.fill_buf()?;
+        let length = available.len().min(buf.len());
+        buf[..length].copy_from_slice(&available[..length]);
+        self.consume(length);
+        Ok(length)
+    }
+}
+
+impl<R: Read> BufRead for BGZFReader<R> {
+    fn fill_buf(&mut self) -> io::Result<&[u8]> {
+        while self.position >= self.buffer.len() {
+            if !self.read_block()? {
+                break;
+            }
+        }
+        Ok(&self.buffer[self.position.min(self.buffer.len())..])
+    }
+
+    fn consume(&mut self, amt: usize) {
+        self.position = (self.position + amt).min(self.buffer.len());
+    }
+}
+
+fn find_block_size(extra: &[u8]) -> Option<usize> {
+    let mut rest = extra;
+    while rest.len() >= 4 {
+        let subfield_length = usize::from(u16::from_le_bytes([rest[2], rest[3]]));
+        if rest[0] == b'B' && rest[1] == b'C' && subfield_length == 2 && rest.len() >= 6 {
+            return Some(usize::from(u16::from_le_bytes([rest[4], rest[5]])) + 1);
+        }
+        rest = rest.get(4 + subfield_length..)?;
+    }
+    None
+}
+
+pub(crate) fn read_exact_or_eof<R: Read>(reader: &mut R, buf: &mut [u8]) -> io::Result<bool> {
+    let mut filled = 0;
+    while filled < buf.len() {
+        match reader.read(&mut buf[filled..]) {
+            Ok(0) if filled == 0 => return Ok(false),
+            Ok(0) => return Err(io::ErrorKind::UnexpectedEof.into()),
+            Ok(n) => filled += n,
+            Err(e) if e.kind() == io::ErrorKind::Interrupted => {}
+            Err(e) => return Err(e),
+        }
+    }
+    Ok(true)
+}
+
+fn invalid_data(message: &'static str) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, message)
+}
+
+#[cfg(test)]
+mod test;
@@ -0,0 +1,129 @@
+use super::{decompress_block, read_raw_block, VirtualOffset};
+use flate2::Decompress;
+use rayon::prelude::*;
+use std::io::{self, BufRead, Read};
+
+/// BGZF block reader which decompresses blocks in parallel with rayon,
+/// like `bgzip -@`. Bytes are delivered in the order of the file.
+pub struct ParallelBGZFReader<R: Read> {
+    reader: R,
+    blocks: Vec<Block>,
+    block_count: usize,
+    current: usize,
+    position: usize,
+    next_block_address: u64,
+    batch_size: usize,
+}
+
+#[derive(Debug, Default)]
+struct Block {
+    address: u64,
+    size: usize,
+    compressed: Vec<u8>,
+    data: Vec<u8>,
+}
+
+impl<R: Read> ParallelBGZFReader<R> {
+    /// Create reader which decompresses 4 blocks per thread at once.
+    pub fn new(reader: R) -> Self {
+        Self::with_batch_size(reader, rayon::current_num_threads() * 4)
+    }
+
+    /// Create reader which decompresses `batch_size` blocks at once.
+    pub fn with_batch_size(reader: R, batch_size: usize) -> Self {
+        ParallelBGZFReader {
+            reader,
+            blocks: Vec::new(),
+            block_count: 0,
+            current: 0,
+            position: 0,
+            next_block_address: 0,
+            batch_size: batch_size.max(1),
+        }
+    }
+
+    /// Virtual offset of the next byte to be read.
+    pub fn virtual_offset(&self) -> VirtualOffset {
+        match self.blocks[..self.block_count].get(self.current) {
+            Some(block) if self.position < block.data.len() => {
+                VirtualOffset::new(block.address, self.position as u16)
+            }
+            Some(block) => VirtualOffset::new(block.address + block.size as u64, 0),
+            None => VirtualOffset::new(self.next_block_address, 0),
+        }
+    }
+
+    /// Read next blocks and decompress them in parallel.
+    /// Return false if no block was remained.
+    fn read_batch(&mut self) -> io::Result<bool> {
+        if self.blocks.len() < self.batch_size {
+            self.blocks.resize_with(self.batch_size, Block::default);
+        }
+        let mut count = 0;
+        while count < self.batch_size {
+            let block = &mut self.blocks[count];
+            match read_raw_block(&mut self.reader, &mut block.compressed)? {
+                Some(size) => {
+                    block.address = self.next_block_address;
+                    block.size = size;
+                    self.next_block_address += size as u64;
+                    count += 1;
+                }
+                None => break,
+            }
+        }
+
+        self.blocks[..count]
+            .par_iter_mut()
+            .map_init(
+                || Decompress::new(false),
+                |decompress, block| {
+                    decompress_block(&block.compressed, &mut block.data, decompress)
+                },
+            )
+            .collect::<Vec<_>>()
+            .into_iter()
+            .collect::<io::Result<()>>()?;
+
+        self.block_count = count;
+        self.current = 0;
+        self.position = 0;
+        Ok(count > 0)
+    }
+}
+
+impl<R: Read> Read for ParallelBGZFReader<R> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        let available = self.fill_buf()?;
+        let length = available.len().min(buf.len());
+        buf[..length].copy_from_slice(&available[..length]);
+        self.consume(length);
+        Ok(length)
+    }
+}
+
+impl<R: Read> BufRead for ParallelBGZFReader<R> {
+    fn fill_buf(&mut self) -> io::Result<&[u8]> {
+        loop {
+            match self.blocks[..self.block_count].get(self.current) {
+                Some(block) if self.position < block.data.len() => break,
+                Some(_) if self.current + 1 < self.block_count => {
+                    self.current += 1;
+                    self.position = 0;
+                }
+                _ => {
+                    if !self.read_batch()? {
+                        return Ok(&[]);
+                    }
+                }
+            }
+        }
+        Ok(&self.blocks[self.current].data[self.position..])
+    }
+
+    fn consume(&mut self, amt: usize) {
+        if let Some(block) = self.blocks[..self.block_count].get(self.current) {
+            self.position = (self.position + amt).min(block.data.len());
+        }
+    }
+}
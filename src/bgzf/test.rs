@@ -0,0 +1,199 @@
+use super::writer::BGZF_EOF;
+use super::*;
+use std::fs::File;
+use std::io::Write;
+
+#[test]
+fn test_virtual_offset() {
+    let offset = VirtualOffset::new(28_000, 1_234);
+    assert_eq!(offset.block_address(), 28_000);
+    assert_eq!(offset.offset_in_block(), 1_234);
+    assert_eq!(offset.0, (28_000 << 16) | 1_234);
+    assert!(VirtualOffset::new(1, 0) > VirtualOffset::new(0, 65_535));
+}
+
+#[test]
+fn test_read_all() -> io::Result<()> {
+    let mut reader = BGZFReader::new(File::open("./testfiles/1kGP-subset.vcf.gz")?);
+    let mut data = Vec::new();
+    reader.read_to_end(&mut data)?;
+    assert_eq!(
+        &data[..],
+        &include_bytes!("../../testfiles/1kGP-subset.vcf")[..]
+    );
+    Ok(())
+}
+
+#[test]
+fn test_seek() -> io::Result<()> {
+    let mut reader = BGZFReader::new(File::open("./testfiles/1kGP-subset.vcf.gz")?);
+    let mut offsets = Vec::new();
+    let mut lines = Vec::new();
+    loop {
+        let offset = reader.virtual_offset();
+        let mut line = Vec::new();
+        if reader.read_until(b'\n', &mut line)? == 0 {
+            break;
+        }
+        offsets.push(offset);
+        lines.push(line);
+    }
+    assert_eq!(lines.len(), 128 + 306);
+    assert!(offsets
+        .iter()
+        .any(|x| x.block_address() != offsets[0].block_address()));
+
+    for i in [433, 0, 200, 128, 129, 300].iter() {
+        reader.seek(offsets[*i])?;
+        let mut line = Vec::new();
+        reader.read_until(b'\n', &mut line)?;
+        assert_eq!(line, lines[*i]);
+        assert_eq!(
+            reader.virtual_offset(),
+            offsets
+                .get(*i + 1)
+                .copied()
+                .unwrap_or(reader.virtual_offset())
+        );
+    }
+
+    Ok(())
+}
+
+#[test]
+fn test_invalid_block() {
+    let mut reader = BGZFReader::new(&include_bytes!("../../testfiles/simple1.vcf")[..]);
+    let mut data = Vec::new();
+    assert_eq!(
+        reader.read_to_end(&mut data).unwrap_err().kind(),
+        io::ErrorKind::InvalidData
+    );
+}
+
+#[test]
+fn test_write_read() -> io::Result<()> {
+    let vcf_data = &include_bytes!("../../testfiles/1kGP-subset.vcf")[..];
+    let mut writer = BGZFWriter::new(Vec::new());
+    let mut offsets = Vec::new();
+    for line in vcf_data.split_inclusive(|x| *x == b'\n') {
+        offsets.push(writer.virtual_offset());
+        writer.write_all(line)?;
+    }
+    let compressed = writer.finish()?;
+    assert!(compressed.ends_with(&BGZF_EOF));
+    assert!(compressed.len() < vcf_data.len() / 2);
+
+    let mut data = Vec::new();
+    flate2::read::MultiGzDecoder::new(&compressed[..]).read_to_end(&mut data)?;
+    assert_eq!(data, vcf_data);
+
+    let mut reader = BGZFReader::new(io::Cursor::new(&compressed));
+    let mut data = Vec::new();
+    reader.read_to_end(&mut data)?;
+    assert_eq!(data, vcf_data);
+
+    let lines: Vec<_> = vcf_data.split_inclusive(|x| *x == b'\n').collect();
+    assert!(offsets
+        .iter()
+        .any(|x| x.block_address() != offsets[0].block_address()));
+    for i in [300, 0, 433].iter() {
+        reader.seek(offsets[*i])?;
+        let mut line = Vec::new();
+        reader.read_until(b'\n', &mut line)?;
+        assert_eq!(line, lines[*i]);
+    }
+
+    Ok(())
+}
+
+#[test]
+fn test_write_incompressible() -> io::Result<()> {
+    let mut state: u32 = 12345;
+    let data: Vec<u8> = (0..200_000)
+        .map(|_| {
+            state = state.wrapping_mul(1_103_515_245).wrapping_add(12_345);
+            (state >> 16) as u8
+        })
+        .collect();
+    let mut writer = BGZFWriter::with_compression_level(Vec::new(), 9);
+    writer.write_all(&data)?;
+    writer.flush()?;
+    writer.write_all(b"")?;
+    let compressed = writer.finish()?;
+
+    let mut reader = BGZFReader::new(&compressed[..]);
+    let mut decompressed = Vec::new();
+    reader.read_to_end(&mut decompressed)?;
+    assert_eq!(decompressed, data);
+
+    assert_eq!(BGZFWriter::new(Vec::new()).finish()?, BGZF_EOF.to_vec());
+    Ok(())
+}
+
+#[cfg(feature = "parallel")]
+#[test]
+fn test_parallel_reader() -> io::Result<()> {
+    let vcf_data = &include_bytes!("../../testfiles/1kGP-subset.vcf")[..];
+    let mut writer = BGZFWriter::new(Vec::new());
+    for _ in 0..10 {
+        writer.write_all(vcf_data)?;
+    }
+    let compressed = writer.finish()?;
+
+    let mut reader = BGZFReader::new(&compressed[..]);
+    let mut expected = Vec::new();
+    loop {
+        let offset = reader.virtual_offset();
+        let mut line = Vec::new();
+        if reader.read_until(b'\n', &mut line)? == 0 {
+            break;
+        }
+        expected.push((offset, line));
+    }
+    assert_eq!(expected.len(), (128 + 306) * 10);
+    assert!(expected.last().unwrap().0.block_address() > 0);
+
+    for batch_size in [1, 3, 0].iter() {
+        let mut reader = if *batch_size == 0 {
+            ParallelBGZFReader::new(&compressed[..])
+        } else {
+            ParallelBGZFReader::with_batch_size(&compressed[..], *batch_size)
+        };
+        let mut lines = Vec::new();
+        loop {
+            let offset = reader.virtual_offset();
+            let mut line = Vec::new();
+            if reader.read_until(b'\n', &mut line)? == 0 {
+                break;
+            }
+            lines.push((offset, line));
+        }
+        assert_eq!(lines, expected);
+        assert_eq!(
+            reader.virtual_offset(),
+            VirtualOffset::new(compressed.len() as u64, 0)
+        );
+    }
+
+    let mut reader = ParallelBGZFReader::new(File::open("./testfiles/1kGP-subset.vcf.gz")?);
+    let mut data = Vec::new();
+    reader.read_to_end(&mut data)?;
+    assert_eq!(data, vcf_data);
+
+    let mut reader = ParallelBGZFReader::new(&include_bytes!("../../testfiles/simple1.vcf")[..]);
+    let mut data = Vec::new();
+    assert_eq!(
+        reader.read_to_end(&mut data).unwrap_err().kind(),
+        io::ErrorKind::InvalidData
+    );
+
+    let mut corrupted = compressed.clone();
+    corrupted[100] ^= 0xff;
+    let mut reader = ParallelBGZFReader::with_batch_size(&corrupted[..], 4);
+    let mut data = Vec::new();
+    assert_eq!(
+        reader.read_to_end(&mut data).unwrap_err().kind(),
+        io::ErrorKind::InvalidData
+    );
+    Ok(())
+}
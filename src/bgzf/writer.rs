@@ -0,0 +1,141 @@
+use super::{VirtualOffset, BGZF_FOOTER_SIZE};
+use flate2::{Compress, Compression, Crc, FlushCompress, Status};
+use std::io::{self, Write};
+
+/// Maximum uncompressed size of a block. Same as htslib.
+const BGZF_BLOCK_SIZE: usize = 0xff00;
+const BGZF_MAX_BLOCK_SIZE: usize = 0x10000;
+const BGZF_HEADER_SIZE: usize = 18;
+
+/// Empty block which marks the end of a BGZF file.
+pub const BGZF_EOF: [u8; 28] = [
+    0x1f, 0x8b, 0x08, 0x04, 0x00, 0x00, 0x00, 0x00, 0x00, 0xff, 0x06, 0x00, 0x42, 0x43, 0x02, 0x00,
+    0x1b, 0x00, 0x03, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00,
+];
+
+/// BGZF block writer.
+///
+/// Data is written as blocks of up to 64 KiB, followed by the EOF marker block
+/// when the writer is finished or dropped.
+pub struct BGZFWriter<W: Write> {
+    writer: Option<W>,
+    block_address: u64,
+    buffer: Vec<u8>,
+    compressed: Vec<u8>,
+    compress: Compress,
+    finished: bool,
+}
+
+impl<W: Write> BGZFWriter<W> {
+    pub fn new(writer: W) -> Self {
+        BGZFWriter::with_compression_level(writer, Compression::default().level())
+    }
+
+    /// Create writer with compression level from 0 (no compression) to 9.
+    pub fn with_compression_level(writer: W, level: u32) -> Self {
+        BGZFWriter {
+            writer: Some(writer),
+            block_address: 0,
+            buffer: Vec::with_capacity(BGZF_BLOCK_SIZE),
+            compressed: Vec::with_capacity(BGZF_MAX_BLOCK_SIZE),
+            compress: Compress::new(Compression::new(level.min(9)), false),
+            finished: false,
+        }
+    }
+
+    /// Virtual offset of the next byte to be written.
+    pub fn virtual_offset(&self) -> VirtualOffset {
+        VirtualOffset::new(self.block_address, self.buffer.len() as u16)
+    }
+
+    pub fn get_ref(&self) -> &W {
+        self.writer.as_ref().unwrap()
+    }
+
+    /// Write remaining data and EOF marker block, then return the inner writer.
+    pub fn finish(mut self) -> io::Result<W> {
+        self.try_finish()?;
+        Ok(self.writer.take().unwrap())
+    }
+
+    /// Write remaining data and EOF marker block.
+    /// No more data should be written after this call.
+    pub fn try_finish(&mut self) -> io::Result<()> {
+        if self.writer.is_none() || self.finished {
+            return Ok(());
+        }
+        self.write_block()?;
+        let writer = self.writer.as_mut().unwrap();
+        writer.write_all(&BGZF_EOF)?;
+        writer.flush()?;
+        self.finished = true;
+        Ok(())
+    }
+
+    /// Compress buffered data into a block and write it.
+    fn write_block(&mut self) -> io::Result<()> {
+        if self.buffer.is_empty() {
+            return Ok(());
+        }
+
+        self.compressed.clear();
+        self.compressed.resize(BGZF_HEADER_SIZE, 0);
+        self.compress.reset();
+        let status = self.compress.compress_vec(
+            &self.buffer,
+            &mut self.compressed,
+            FlushCompress::Finish,
+        )?;
+        if status != Status::StreamEnd
+            || self.compressed.len() + BGZF_FOOTER_SIZE > BGZF_MAX_BLOCK_SIZE
+        {
+            // Incompressible data may become larger than a block. Store it without compression.
+            self.compressed.truncate(BGZF_HEADER_SIZE);
+            Compress::new(Compression::none(), false).compress_vec(
+                &self.buffer,
+                &mut self.compressed,
+                FlushCompress::Finish,
+            )?;
+        }
+
+        let mut crc = Crc::new();
+        crc.update(&self.buffer);
+        self.compressed.extend_from_slice(&crc.sum().to_le_bytes());
+        self.compressed
+            .extend_from_slice(&(self.buffer.len() as u32).to_le_bytes());
+        let block_size = self.compressed.len();
+        self.compressed[..16].copy_from_slice(&[
+            0x1f, 0x8b, 0x08, 0x04, 0x00, 0x00, 0x00, 0x00, 0x00, 0xff, 0x06, 0x00, 0x42, 0x43,
+            0x02, 0x00,
+        ]);
+        self.compressed[16..18].copy_from_slice(&((block_size - 1) as u16).to_le_bytes());
+
+        self.writer.as_mut().unwrap().write_all(&self.compressed)?;
+        self.block_address += block_size as u64;
+        self.buffer.clear();
+        Ok(())
+    }
+}
+
+impl<W: Write> Write for BGZFWriter<W> {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        if self.buffer.len() >= BGZF_BLOCK_SIZE {
+            self.write_block()?;
+        }
+        let length = buf.len().min(BGZF_BLOCK_SIZE - self.buffer.len());
+        self.buffer.extend_from_slice(&buf[..length]);
+        Ok(length)
+    }
+
+    /// Write buffered data as a block and flush the inner writer.
+    fn flush(&mut self) -> io::Result<()> {
+        self.write_block()?;
+        self.writer.as_mut().unwrap().flush()
+    }
+}
+
+impl<W: Write> Drop for BGZFWriter<W> {
+    fn drop(&mut self) {
+        let _ = self.try_finish();
+    }
+}
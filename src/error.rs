@@ -1,13 +1,114 @@
+use std::fmt;
 use thiserror::Error;
 
+/// Location and cause of a header or record parse error.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ParseErrorDetail {
+    /// 1-based line number. `None` if it is unknown, such as for records
+    /// read with `IndexedVCFReader`.
+    pub line: Option<u64>,
+    /// 1-based byte column in the line.
+    pub column: usize,
+    /// Failed field such as `POS`, `QUAL` or `INFO/DP`.
+    pub field: crate::U8Vec,
+    /// Offending text.
+    pub text: crate::U8Vec,
+    /// Human-readable reason.
+    pub reason: &'static str,
+}
+
+impl ParseErrorDetail {
+    pub(crate) fn new(
+        line: u64,
+        column: usize,
+        field: &[u8],
+        text: &[u8],
+        reason: &'static str,
+    ) -> Self {
+        ParseErrorDetail {
+            line: Some(line),
+            column,
+            field: field.to_vec(),
+            text: text.to_vec(),
+            reason,
+        }
+    }
+}
+
+impl fmt::Display for ParseErrorDetail {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if let Some(line) = self.line {
+            write!(f, "line: {}, ", line)?;
+        }
+        write!(
+            f,
+            "column: {}, {}: {} ({:?})",
+            self.column,
+            String::from_utf8_lossy(&self.field),
+            self.reason,
+            String::from_utf8_lossy(&self.text)
+        )
+    }
+}
+
 #[derive(Error, Debug)]
 pub enum VCFError {
-    #[error("Failed to parse header at line: {}", _0)]
-    HeaderParseError(u64),
-    #[error("Failed to parse record at line: {}", _0)]
-    RecordParseError(u64),
+    #[error("Failed to parse header at {}", _0)]
+    HeaderParseError(ParseErrorDetail),
+    #[error("Failed to parse record at {}", _0)]
+    RecordParseError(ParseErrorDetail),
+    #[error("Failed to parse index: {}", _0)]
+    IndexParseError(&'static str),
+    #[error("Invalid argument: {}", _0)]
+    InvalidArgument(&'static str),
+    #[error("Records are not sorted at: {}:{}", String::from_utf8_lossy(_0), _1)]
+    UnsortedRecord(crate::U8Vec, u64),
+    #[error(
+        "Position is out of index range at: {}:{}",
+        String::from_utf8_lossy(_0),
+        _1
+    )]
+    OutOfIndexRange(crate::U8Vec, u64),
+    #[error("Failed to parse BCF: {}", _0)]
+    BCFParseError(&'static str),
+    #[error("Failed to encode BCF: {}", _0)]
+    BCFEncodeError(&'static str),
+    #[error(
+        "Value of {} does not match header: {}",
+        String::from_utf8_lossy(_0),
+        _1
+    )]
+    TypedValueError(crate::U8Vec, &'static str),
+    #[error("Failed to parse FASTA index at line: {}", _0)]
+    FastaIndexParseError(u64),
+    #[error("Unknown sequence: {}", String::from_utf8_lossy(_0))]
+    UnknownSequence(crate::U8Vec),
+    #[error(
+        "REF does not match the reference genome at: {}:{}",
+        String::from_utf8_lossy(_0),
+        _1
+    )]
+    ReferenceMismatch(crate::U8Vec, u64),
+    #[error("Unknown sample: {}", String::from_utf8_lossy(_0))]
+    UnknownSample(crate::U8Vec),
+    #[error("Sample index is out of range: {}", _0)]
+    SampleIndexOutOfRange(usize),
+    #[error("Invalid header value: {} ({})", String::from_utf8_lossy(_0), _1)]
+    InvalidHeaderValue(crate::U8Vec, &'static str),
     #[error("I/O Error")]
     IoError(#[from] std::io::Error),
     #[error("Utf8 Error")]
     Utf8Error(#[from] std::str::Utf8Error),
 }
+
+impl VCFError {
+    /// Forget the line number of a parse error, for lines read at an unknown position.
+    pub(crate) fn without_line(mut self) -> Self {
+        match &mut self {
+            VCFError::HeaderParseError(x) => x.line = None,
+            VCFError::RecordParseError(x) => x.line = None,
+            _ => (),
+        }
+        self
+    }
+}
@@ -0,0 +1,227 @@
+use super::{Number, VCFHeader, VCFHeaderLine, VCFVersion, ValueType};
+use crate::{U8Vec, VCFError};
+
+/// Builder of `VCFHeader` from typed values.
+/// Header lines are rendered with quoted and escaped descriptions in the order of calls.
+/// Escapes are removed again in descriptions of the built header.
+///
+/// ```
+/// use vcf::{Number, VCFHeaderBuilder, VCFVersion, ValueType};
+///
+/// let header = VCFHeaderBuilder::new(VCFVersion::Vcf4_2)
+///     .contig(b"1", Some(249250621))
+///     .info(b"DP", Number::Number(1), ValueType::Integer, b"Total depth")
+///     .format(b"GT", Number::Number(1), ValueType::String, b"Genotype")
+///     .filter(b"q10", b"Quality below 10")
+///     .sample(b"NA12878")
+///     .build()
+///     .unwrap();
+/// assert_eq!(header.info(b"DP").unwrap().description, b"Total depth");
+/// assert_eq!(header.samples(), &[b"NA12878".to_vec()]);
+/// ```
+#[derive(Debug, Clone)]
+pub struct VCFHeaderBuilder {
+    lines: Vec<U8Vec>,
+    samples: Vec<U8Vec>,
+    error: Option<(U8Vec, &'static str)>,
+}
+
+impl VCFHeaderBuilder {
+    /// Start a header with `##fileformat` line.
+    pub fn new(version: VCFVersion) -> Self {
+        let version: &[u8] = match &version {
+            VCFVersion::Vcf4_3 => b"VCFv4.3",
+            VCFVersion::Vcf4_2 => b"VCFv4.2",
+            VCFVersion::Vcf4_1 => b"VCFv4.1",
+            VCFVersion::Vcf4_0 => b"VCFv4.0",
+            VCFVersion::Other(x) => x,
+        };
+        let mut line = b"##fileformat=".to_vec();
+        line.extend_from_slice(version);
+        line.push(b'\n');
+        VCFHeaderBuilder {
+            lines: vec![line],
+            samples: Vec::new(),
+            error: None,
+        }
+    }
+
+    pub fn contig(self, id: &[u8], length: Option<u64>) -> Self {
+        let length = length.map(|x| x.to_string().into_bytes());
+        let mut entries = vec![(&b"ID"[..], Entry::Plain(id))];
+        if let Some(x) = length.as_ref() {
+            entries.push((b"length", Entry::Plain(x)));
+        }
+        self.structured(b"contig", &entries)
+    }
+
+    pub fn info(
+        self,
+        id: &[u8],
+        number: Number,
+        value_type: ValueType,
+        description: &[u8],
+    ) -> Self {
+        self.info_format(b"INFO", id, &number, &value_type, description)
+    }
+
+    pub fn format(
+        self,
+        id: &[u8],
+        number: Number,
+        value_type: ValueType,
+        description: &[u8],
+    ) -> Self {
+        self.info_format(b"FORMAT", id, &number, &value_type, description)
+    }
+
+    pub fn filter(self, id: &[u8], description: &[u8]) -> Self {
+        self.structured(
+            b"FILTER",
+            &[
+                (b"ID", Entry::Plain(id)),
+                (b"Description", Entry::Quoted(description)),
+            ],
+        )
+    }
+
+    pub fn alt(self, id: &[u8], description: &[u8]) -> Self {
+        self.structured(
+            b"ALT",
+            &[
+                (b"ID", Entry::Plain(id)),
+                (b"Description", Entry::Quoted(description)),
+            ],
+        )
+    }
+
+    /// Add an unstructured line such as `##source=program`.
+    pub fn other(mut self, key: &[u8], value: &[u8]) -> Self {
+        self.check(key, b"=<>\r\n");
+        self.check(value, b"\r\n");
+        let mut line = b"##".to_vec();
+        line.extend_from_slice(key);
+        line.push(b'=');
+        line.extend_from_slice(value);
+        line.push(b'\n');
+        self.lines.push(line);
+        self
+    }
+
+    pub fn sample(mut self, name: &[u8]) -> Self {
+        self.check(name, b"\t\r\n");
+        self.samples.push(name.to_vec());
+        self
+    }
+
+    pub fn samples<T: AsRef<[u8]>>(self, names: &[T]) -> Self {
+        names.iter().fold(self, |x, y| x.sample(y.as_ref()))
+    }
+
+    /// Parse rendered lines and create a header.
+    /// Return `InvalidHeaderValue` if an ID or a name contains a character which cannot be written.
+    pub fn build(self) -> Result<VCFHeader, VCFError> {
+        if let Some((value, reason)) = self.error {
+            return Err(VCFError::InvalidHeaderValue(value, reason));
+        }
+        let items = self
+            .lines
+            .iter()
+            .enumerate()
+            .map(|(i, x)| VCFHeaderLine::from_bytes(x, i as u64 + 1))
+            .collect::<Result<Vec<_>, _>>()?;
+        Ok(VCFHeader::new(items, self.samples))
+    }
+
+    fn info_format(
+        self,
+        key: &[u8],
+        id: &[u8],
+        number: &Number,
+        value_type: &ValueType,
+        description: &[u8],
+    ) -> Self {
+        let number = match number {
+            Number::Reference => b"R".to_vec(),
+            Number::Allele => b"A".to_vec(),
+            Number::Genotype => b"G".to_vec(),
+            Number::Zero => b"0".to_vec(),
+            Number::Number(x) => x.to_string().into_bytes(),
+            Number::Unknown => b".".to_vec(),
+            Number::Other(x) => x.clone(),
+        };
+        let value_type: &[u8] = match value_type {
+            ValueType::String => b"String",
+            ValueType::Integer => b"Integer",
+            ValueType::Flag => b"Flag",
+            ValueType::Character => b"Character",
+            ValueType::Float => b"Float",
+            ValueType::Other(x) => x,
+        };
+        self.structured(
+            key,
+            &[
+                (b"ID", Entry::Plain(id)),
+                (b"Number", Entry::Plain(&number)),
+                (b"Type", Entry::Plain(value_type)),
+                (b"Description", Entry::Quoted(description)),
+            ],
+        )
+    }
+
+    fn structured(mut self, key: &[u8], entries: &[(&[u8], Entry<'_>)]) -> Self {
+        let mut line = b"##".to_vec();
+        line.extend_from_slice(key);
+        line.extend_from_slice(b"=<");
+        for (i, (key, value)) in entries.iter().enumerate() {
+            if i != 0 {
+                line.push(b',');
+            }
+            line.extend_from_slice(key);
+            line.push(b'=');
+            match value {
+                Entry::Plain(x) => {
+                    self.check(x, b">,= \"\r\n\t");
+                    line.extend_from_slice(x);
+                }
+                Entry::Quoted(x) => {
+                    if x.iter().any(|y| *y == b'\r' || *y == b'\n') {
+                        self.set_error(x, "Forbidden character");
+                    }
+                    line.push(b'"');
+                    for one in x.iter() {
+                        if *one == b'"' || *one == b'\\' {
+                            line.push(b'\\');
+                        }
+                        line.push(*one);
+                    }
+                    line.push(b'"');
+                }
+            }
+        }
+        line.extend_from_slice(b">\n");
+        self.lines.push(line);
+        self
+    }
+
+    /// Check that `value` is not empty and does not contain `forbidden` characters.
+    fn check(&mut self, value: &[u8], forbidden: &[u8]) {
+        if value.is_empty() {
+            self.set_error(value, "Empty value");
+        } else if value.iter().any(|x| forbidden.contains(x)) {
+            self.set_error(value, "Forbidden character");
+        }
+    }
+
+    /// Remember the first error to return it from `build`.
+    fn set_error(&mut self, value: &[u8], reason: &'static str) {
+        if self.error.is_none() {
+            self.error = Some((value.to_vec(), reason));
+        }
+    }
+}
+
+enum Entry<'a> {
+    Plain(&'a [u8]),
+    Quoted(&'a [u8]),
+}
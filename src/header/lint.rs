@@ -0,0 +1,246 @@
+use super::{Number, VCFHeader, VCFHeaderContent, VCFHeaderLine, VCFVersion, ValueType};
+use std::collections::HashSet;
+use std::fmt;
+
+/// A problem of a header found by `lint_header`.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct HeaderViolation {
+    /// Offending header line. `None` if a required line is missing.
+    pub line: Option<VCFHeaderLine>,
+    /// Human-readable reason.
+    pub reason: &'static str,
+}
+
+impl fmt::Display for HeaderViolation {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match &self.line {
+            Some(x) => write!(
+                f,
+                "{}: {}",
+                self.reason,
+                String::from_utf8_lossy(x.line()).trim_end()
+            ),
+            None => write!(f, "{}", self.reason),
+        }
+    }
+}
+
+/// Reserved INFO keys with their `Number`, `Type` and the first version which defines them.
+const RESERVED_INFO: &[(&[u8], Number, ValueType, u8)] = &[
+    (b"AA", Number::Number(1), ValueType::String, 0),
+    (b"AC", Number::Allele, ValueType::Integer, 1),
+    (b"AD", Number::Reference, ValueType::Integer, 3),
+    (b"ADF", Number::Reference, ValueType::Integer, 3),
+    (b"ADR", Number::Reference, ValueType::Integer, 3),
+    (b"AF", Number::Allele, ValueType::Float, 1),
+    (b"AN", Number::Number(1), ValueType::Integer, 0),
+    (b"BQ", Number::Number(1), ValueType::Float, 0),
+    (b"CIGAR", Number::Allele, ValueType::String, 1),
+    (b"DB", Number::Zero, ValueType::Flag, 0),
+    (b"DP", Number::Number(1), ValueType::Integer, 0),
+    (b"END", Number::Number(1), ValueType::Integer, 0),
+    (b"H2", Number::Zero, ValueType::Flag, 0),
+    (b"H3", Number::Zero, ValueType::Flag, 0),
+    (b"MQ", Number::Number(1), ValueType::Float, 0),
+    (b"MQ0", Number::Number(1), ValueType::Integer, 0),
+    (b"NS", Number::Number(1), ValueType::Integer, 0),
+    (b"SB", Number::Number(4), ValueType::Integer, 3),
+    (b"SOMATIC", Number::Zero, ValueType::Flag, 0),
+    (b"VALIDATED", Number::Zero, ValueType::Flag, 0),
+    (b"1000G", Number::Zero, ValueType::Flag, 0),
+];
+
+/// Reserved FORMAT keys with their `Number`, `Type` and the first version which defines them.
+const RESERVED_FORMAT: &[(&[u8], Number, ValueType, u8)] = &[
+    (b"AD", Number::Reference, ValueType::Integer, 3),
+    (b"ADF", Number::Reference, ValueType::Integer, 3),
+    (b"ADR", Number::Reference, ValueType::Integer, 3),
+    (b"DP", Number::Number(1), ValueType::Integer, 0),
+    (b"EC", Number::Allele, ValueType::Integer, 1),
+    (b"FT", Number::Number(1), ValueType::String, 0),
+    (b"GL", Number::Genotype, ValueType::Float, 1),
+    (b"GP", Number::Genotype, ValueType::Float, 1),
+    (b"GQ", Number::Number(1), ValueType::Integer, 0),
+    (b"GT", Number::Number(1), ValueType::String, 0),
+    (b"HQ", Number::Number(2), ValueType::Integer, 0),
+    (b"MQ", Number::Number(1), ValueType::Integer, 0),
+    (b"PL", Number::Genotype, ValueType::Integer, 1),
+    (b"PQ", Number::Number(1), ValueType::Integer, 0),
+    (b"PS", Number::Number(1), ValueType::Integer, 0),
+];
+
+/// Minor version of VCF 4.x. Unknown versions are treated as the latest one.
+fn minor_version(version: &VCFVersion) -> u8 {
+    match version {
+        VCFVersion::Vcf4_0 => 0,
+        VCFVersion::Vcf4_1 => 1,
+        VCFVersion::Vcf4_2 => 2,
+        VCFVersion::Vcf4_3 | VCFVersion::Other(_) => 3,
+    }
+}
+
+/// Check `header` against the rules of its `fileformat` version.
+/// Duplicated IDs, reserved keys with wrong `Number` or `Type`, and structured
+/// lines without required fields such as `ID` are reported.
+/// Return an empty list if no problem was found.
+pub fn lint_header(header: &VCFHeader) -> Vec<HeaderViolation> {
+    let mut violations = Vec::new();
+    let mut push = |line: &VCFHeaderLine, reason| {
+        violations.push(HeaderViolation {
+            line: Some(line.clone()),
+            reason,
+        })
+    };
+
+    // Lines before fileformat are also checked against its version
+    let file_format = header.items().iter().find_map(|x| match x.contents() {
+        VCFHeaderContent::FileFormat(x) => Some(x),
+        _ => None,
+    });
+    let version = file_format.map(minor_version).unwrap_or(3);
+    let mut file_format_found = false;
+    let mut ids = HashSet::new();
+    for (i, line) in header.items().iter().enumerate() {
+        match line.contents() {
+            VCFHeaderContent::FileFormat(x) => {
+                if file_format_found {
+                    push(line, "Duplicated fileformat line");
+                    continue;
+                }
+                file_format_found = true;
+                if i != 0 {
+                    push(line, "fileformat is not the first line");
+                }
+                if let VCFVersion::Other(_) = x {
+                    push(line, "Unknown VCF version");
+                }
+            }
+            VCFHeaderContent::INFO {
+                id,
+                number,
+                value_type,
+                ..
+            } => {
+                if !ids.insert((&b"INFO"[..], id)) {
+                    push(line, "Duplicated INFO ID");
+                }
+                for reason in check_definition(RESERVED_INFO, id, number, value_type, version) {
+                    push(line, reason);
+                }
+            }
+            VCFHeaderContent::FORMAT {
+                id,
+                number,
+                value_type,
+                ..
+            } => {
+                if !ids.insert((&b"FORMAT"[..], id)) {
+                    push(line, "Duplicated FORMAT ID");
+                }
+                if *value_type == ValueType::Flag {
+                    push(line, "FORMAT cannot be Flag");
+                }
+                for reason in check_definition(RESERVED_FORMAT, id, number, value_type, version) {
+                    push(line, reason);
+                }
+            }
+            VCFHeaderContent::FILTER { id, .. } => {
+                if !ids.insert((&b"FILTER"[..], id)) {
+                    push(line, "Duplicated FILTER ID");
+                }
+            }
+            VCFHeaderContent::ALT { id, .. } => {
+                if !ids.insert((&b"ALT"[..], id)) {
+                    push(line, "Duplicated ALT ID");
+                }
+            }
+            VCFHeaderContent::Contig { id, .. } => {
+                if !ids.insert((&b"contig"[..], id)) {
+                    push(line, "Duplicated contig ID");
+                }
+            }
+            VCFHeaderContent::Other => {
+                // Structured lines are parsed as other lines if required fields are missing
+                let structured = [
+                    &b"##INFO="[..],
+                    b"##FORMAT=",
+                    b"##FILTER=",
+                    b"##ALT=",
+                    b"##contig=",
+                ];
+                if structured.iter().any(|x| line.line().starts_with(x)) {
+                    push(line, "Required field is missing");
+                }
+            }
+        }
+    }
+
+    if file_format.is_none() {
+        violations.push(HeaderViolation {
+            line: None,
+            reason: "No fileformat line",
+        });
+    }
+    violations
+}
+
+fn check_definition(
+    reserved: &[(&[u8], Number, ValueType, u8)],
+    id: &[u8],
+    number: &Number,
+    value_type: &ValueType,
+    version: u8,
+) -> Vec<&'static str> {
+    let mut reasons = Vec::new();
+    match number {
+        Number::Allele | Number::Genotype if version < 1 => {
+            reasons.push("Number=A and G are not supported before VCFv4.1")
+        }
+        Number::Reference if version < 2 => {
+            reasons.push("Number=R is not supported before VCFv4.2")
+        }
+        Number::Other(_) => reasons.push("Unknown Number"),
+        _ => (),
+    }
+    match value_type {
+        ValueType::Flag if *number != Number::Zero => reasons.push("Flag must have Number=0"),
+        ValueType::Other(_) => reasons.push("Unknown Type"),
+        _ => (),
+    }
+    if version >= 3 && !is_valid_id(id) {
+        reasons.push("Invalid ID");
+    }
+    if let Some((_, reserved_number, reserved_type, _)) = reserved
+        .iter()
+        .find(|(key, _, _, since)| *key == id && *since <= version)
+    {
+        if number != reserved_number {
+            reasons.push("Reserved key has wrong Number");
+        }
+        if value_type != reserved_type {
+            reasons.push("Reserved key has wrong Type");
+        }
+    }
+    reasons
+}
+
+/// INFO and FORMAT IDs must match `^([A-Za-z_][0-9A-Za-z_.]*|1000G)$` since VCFv4.3.
+fn is_valid_id(id: &[u8]) -> bool {
+    match id.split_first() {
+        _ if id == b"1000G" => true,
+        Some((first, rest)) => {
+            (first.is_ascii_alphabetic() || *first == b'_')
+                && rest
+                    .iter()
+                    .all(|x| x.is_ascii_alphanumeric() || *x == b'_' || *x == b'.')
+        }
+        None => false,
+    }
+}
+
+impl VCFHeader {
+    /// Check this header. See `lint_header`.
+    pub fn lint(&self) -> Vec<HeaderViolation> {
+        lint_header(self)
+    }
+}
@@ -1,9 +1,13 @@
 use super::{U8Vec, VCFError, VResult};
 use std::collections::{hash_map::Keys, HashMap};
 use std::io::BufRead;
-use std::str::FromStr;
+use std::str::{self, FromStr};
+mod builder;
+mod lint;
 mod parser;
 
+pub use builder::VCFHeaderBuilder;
+pub use lint::{lint_header, HeaderViolation};
 pub use parser::parse_header_item;
 
 /// A number of entries of INFO or FORMAT.
@@ -89,6 +93,12 @@ pub struct VCFHeaderFilterAlt<'a> {
     pub description: &'a [u8],
 }
 
+#[derive(Debug, Clone, Hash, PartialEq, Eq, PartialOrd, Ord)]
+pub struct VCFHeaderContig<'a> {
+    pub id: &'a [u8],
+    pub length: Option<u64>,
+}
+
 /// A header line.
 #[derive(Debug, Clone, Hash, PartialEq, Eq, PartialOrd, Ord)]
 pub struct VCFHeaderLine {
@@ -99,7 +109,7 @@ pub struct VCFHeaderLine {
 impl VCFHeaderLine {
     pub fn from_bytes(line: &[u8], line_num: u64) -> Result<Self, VCFError> {
         parse_header_item(line)
-            .map_err(|_| VCFError::HeaderParseError(line_num))
+            .map_err(|e| parser::header_error(line, line_num, e))
             .map(|x| x.1)
     }
     pub fn line(&self) -> &[u8] {
@@ -109,6 +119,15 @@ impl VCFHeaderLine {
     pub fn contents(&self) -> &VCFHeaderContent {
         &self.contents
     }
+
+    /// Dictionary index given by `IDX` field. htslib adds it to BCF header.
+    pub(crate) fn idx(&self) -> Option<usize> {
+        let start = self.line.iter().position(|x| *x == b'<')? + 1;
+        let (_, entries) = parser::parse_header_entries(&self.line[start..]).ok()?;
+        parser::find_key(&entries, b"IDX")
+            .and_then(|x| str::from_utf8(x).ok())
+            .and_then(|x| x.parse().ok())
+    }
 }
 
 impl FromStr for VCFHeaderLine {
@@ -116,7 +135,7 @@ impl FromStr for VCFHeaderLine {
 
     fn from_str(s: &str) -> Result<Self, Self::Err> {
         parse_header_item(s.as_bytes())
-            .map_err(|_| VCFError::HeaderParseError(0))
+            .map_err(|e| parser::header_error(s.as_bytes(), 0, e))
             .map(|x| x.1)
     }
 }
@@ -130,6 +149,7 @@ pub struct VCFHeader {
     format_key: HashMap<U8Vec, usize>,
     alt_key: HashMap<U8Vec, usize>,
     filter_key: HashMap<U8Vec, usize>,
+    contig_key: HashMap<U8Vec, usize>,
     sample_to_index: HashMap<U8Vec, usize>,
 }
 
@@ -140,6 +160,7 @@ impl VCFHeader {
             format_key: create_format_key(&items),
             alt_key: create_alt_key(&items),
             filter_key: create_filter_key(&items),
+            contig_key: create_contig_key(&items),
             sample_to_index: samples
                 .iter()
                 .enumerate()
@@ -157,7 +178,7 @@ impl VCFHeader {
         &self.samples
     }
 
-    pub fn info_list(&self) -> Keys<U8Vec, usize> {
+    pub fn info_list(&self) -> Keys<'_, U8Vec, usize> {
         self.info_key.keys()
     }
 
@@ -177,14 +198,14 @@ impl VCFHeader {
                     number,
                     value_type,
                     description,
-                    source: source.as_ref().map(|x| -> &[u8] { &x }),
-                    version: version.as_ref().map(|x| -> &[u8] { &x }),
+                    source: source.as_ref().map(|x| -> &[u8] { x }),
+                    version: version.as_ref().map(|x| -> &[u8] { x }),
                 },
                 _ => unreachable!(),
             })
     }
 
-    pub fn format_list(&self) -> Keys<U8Vec, usize> {
+    pub fn format_list(&self) -> Keys<'_, U8Vec, usize> {
         self.format_key.keys()
     }
     pub fn format<'a>(&'a self, key: &[u8]) -> Option<VCFHeaderInfoFormat<'a>> {
@@ -203,14 +224,14 @@ impl VCFHeader {
                     number,
                     value_type,
                     description,
-                    source: source.as_ref().map(|x| -> &[u8] { &x }),
-                    version: version.as_ref().map(|x| -> &[u8] { &x }),
+                    source: source.as_ref().map(|x| -> &[u8] { x }),
+                    version: version.as_ref().map(|x| -> &[u8] { x }),
                 },
                 _ => unreachable!(),
             })
     }
 
-    pub fn alt_list(&self) -> Keys<U8Vec, usize> {
+    pub fn alt_list(&self) -> Keys<'_, U8Vec, usize> {
         self.alt_key.keys()
     }
 
@@ -223,7 +244,7 @@ impl VCFHeader {
             })
     }
 
-    pub fn filter_list(&self) -> Keys<U8Vec, usize> {
+    pub fn filter_list(&self) -> Keys<'_, U8Vec, usize> {
         self.filter_key.keys()
     }
 
@@ -238,6 +259,22 @@ impl VCFHeader {
             })
     }
 
+    pub fn contig_list(&self) -> Keys<'_, U8Vec, usize> {
+        self.contig_key.keys()
+    }
+
+    pub fn contig<'a>(&'a self, key: &[u8]) -> Option<VCFHeaderContig<'a>> {
+        self.contig_key
+            .get(key)
+            .map(|x| match &self.items[*x].contents() {
+                VCFHeaderContent::Contig { id, length } => VCFHeaderContig {
+                    id,
+                    length: *length,
+                },
+                _ => unreachable!(),
+            })
+    }
+
     pub fn sample_index(&self, sample_name: &[u8]) -> Option<usize> {
         self.sample_to_index.get(sample_name).cloned()
     }
@@ -287,27 +324,39 @@ fn create_filter_key(header_line: &[VCFHeaderLine]) -> HashMap<U8Vec, usize> {
         .collect()
 }
 
-pub fn parse_header<R: BufRead>(
-    reader: &mut R,
-) -> Result<(u64, Option<U8Vec>, VCFHeader), VCFError> {
+fn create_contig_key(header_line: &[VCFHeaderLine]) -> HashMap<U8Vec, usize> {
+    header_line
+        .iter()
+        .enumerate()
+        .filter_map(|x| match &x.1.contents {
+            VCFHeaderContent::Contig { id, .. } => Some((id.to_vec(), x.0)),
+            _ => None,
+        })
+        .collect()
+}
+
+/// Parse header lines and return the number of consumed lines and the header.
+/// The first record line is left unread in the reader.
+pub fn parse_header<R: BufRead>(reader: &mut R) -> Result<(u64, VCFHeader), VCFError> {
     let mut line_num: u64 = 0;
     let mut items = Vec::new();
 
     loop {
+        if !reader.fill_buf()?.starts_with(b"#") {
+            return Ok((line_num, VCFHeader::new(items, Vec::new())));
+        }
         let mut buffer = Vec::new();
         line_num += 1;
         reader.read_until(b'\n', &mut buffer)?;
         if buffer.starts_with(b"##") {
             let item = parser::parse_header_item(&buffer)
-                .map_err::<VCFError, _>(|_| VCFError::HeaderParseError(line_num))?;
+                .map_err(|e| parser::header_error(&buffer, line_num, e))?;
             items.push(item.1);
-        } else if buffer.starts_with(b"#") {
+        } else {
             let samples = parser::parse_samples(&buffer)
-                .map_err::<VCFError, _>(|_| VCFError::HeaderParseError(line_num))?
+                .map_err(|e| parser::header_error(&buffer, line_num, e))?
                 .1;
-            return Ok((line_num, None, VCFHeader::new(items, samples)));
-        } else {
-            return Ok((line_num, Some(buffer), VCFHeader::new(items, Vec::new())));
+            return Ok((line_num, VCFHeader::new(items, samples)));
         }
     }
 }
@@ -1,7 +1,9 @@
 use super::*;
+use crate::ParseErrorDetail;
 use nom::{
-    self, branch::alt, bytes::complete::is_not, bytes::complete::tag, bytes::complete::take_while,
-    character::is_digit, combinator::eof, combinator::map, combinator::opt, multi::separated_list0,
+    self, branch::alt, bytes::complete::is_not, bytes::complete::tag, bytes::complete::take,
+    bytes::complete::take_while, character::is_digit, combinator::eof, combinator::map,
+    combinator::opt, combinator::recognize, multi::many0, multi::separated_list0,
     sequence::separated_pair, sequence::tuple,
 };
 use std::str;
@@ -28,14 +30,25 @@ pub fn parse_header_content(header_line_without_sharp: &[u8]) -> VResult<&[u8],
     ))(header_line_without_sharp)
 }
 
-pub fn parse_header_entries(value: &[u8]) -> VResult<&[u8], Vec<EntryPair>> {
+pub fn parse_header_entries(value: &[u8]) -> VResult<&[u8], Vec<EntryPair<'_>>> {
     separated_list0(
         tag(b","),
         separated_pair(
             is_not(&b">,= \r\n\t"[..]),
             tag(b"="),
             alt((
-                map(tuple((tag(b"\""), is_not(&b"\""[..]), tag(b"\""))), |x| x.1),
+                // Quoted values may contain escaped quotes and backslashes
+                map(
+                    tuple((
+                        tag(b"\""),
+                        recognize(many0(alt((
+                            is_not(&b"\\\""[..]),
+                            recognize(tuple((tag(b"\\"), take(1usize)))),
+                        )))),
+                        tag(b"\""),
+                    )),
+                    |x| x.1,
+                ),
                 is_not(&b">, \r\n\t"[..]),
             )),
         ),
@@ -62,6 +75,21 @@ pub fn find_key_or_error<'a>(
     })
 }
 
+/// Remove backslashes which escape `"` and `\\` in a quoted value.
+/// Other backslashes are kept as they are.
+pub fn unescape(value: &[u8]) -> U8Vec {
+    let mut result = Vec::with_capacity(value.len());
+    let mut i = 0;
+    while i < value.len() {
+        if value[i] == b'\\' && matches!(value.get(i + 1), Some(b'"') | Some(b'\\')) {
+            i += 1;
+        }
+        result.push(value[i]);
+        i += 1;
+    }
+    result
+}
+
 pub fn parse_number(value: &[u8]) -> Number {
     match value {
         b"R" => Number::Reference,
@@ -132,10 +160,14 @@ pub fn parse_vcf_info_header(header_line: &[u8]) -> VResult<&[u8], VCFHeaderCont
         header_line,
         "No Type tag",
     )?);
-    let description =
-        find_key_or_error(&entries, b"Description", header_line, "No Description tag")?.to_vec();
-    let source = find_key(&entries, b"Source").map(|x| x.to_vec());
-    let version = find_key(&entries, b"Version").map(|x| x.to_vec());
+    let description = unescape(find_key_or_error(
+        &entries,
+        b"Description",
+        header_line,
+        "No Description tag",
+    )?);
+    let source = find_key(&entries, b"Source").map(unescape);
+    let version = find_key(&entries, b"Version").map(unescape);
 
     Ok((
         rest,
@@ -167,10 +199,14 @@ pub fn parse_vcf_format_header(header_line: &[u8]) -> VResult<&[u8], VCFHeaderCo
         header_line,
         "No Type tag",
     )?);
-    let description =
-        find_key_or_error(&entries, b"Description", header_line, "No Description tag")?.to_vec();
-    let source = find_key(&entries, b"Source").map(|x| x.to_vec());
-    let version = find_key(&entries, b"Version").map(|x| x.to_vec());
+    let description = unescape(find_key_or_error(
+        &entries,
+        b"Description",
+        header_line,
+        "No Description tag",
+    )?);
+    let source = find_key(&entries, b"Source").map(unescape);
+    let version = find_key(&entries, b"Version").map(unescape);
 
     Ok((
         rest,
@@ -190,8 +226,12 @@ pub fn parse_vcf_filter_header(header_line: &[u8]) -> VResult<&[u8], VCFHeaderCo
     let (rest, entries) = parse_header_entries(rest)?;
     let (rest, _) = tag(b">")(rest)?;
     let id = find_key_or_error(&entries, b"ID", header_line, "No ID tag")?.to_vec();
-    let description =
-        find_key_or_error(&entries, b"Description", header_line, "No Description tag")?.to_vec();
+    let description = unescape(find_key_or_error(
+        &entries,
+        b"Description",
+        header_line,
+        "No Description tag",
+    )?);
 
     Ok((rest, VCFHeaderContent::FILTER { id, description }))
 }
@@ -201,8 +241,12 @@ pub fn parse_vcf_alt_header(header_line: &[u8]) -> VResult<&[u8], VCFHeaderConte
     let (rest, entries) = parse_header_entries(rest)?;
     let (rest, _) = tag(b">")(rest)?;
     let id = find_key_or_error(&entries, b"ID", header_line, "No ID tag")?.to_vec();
-    let description =
-        find_key_or_error(&entries, b"Description", header_line, "No Description tag")?.to_vec();
+    let description = unescape(find_key_or_error(
+        &entries,
+        b"Description",
+        header_line,
+        "No Description tag",
+    )?);
 
     Ok((rest, VCFHeaderContent::ALT { id, description }))
 }
@@ -236,16 +280,60 @@ pub fn parse_samples(header_line: &[u8]) -> VResult<&[u8], Vec<U8Vec>> {
 
     let samples: Vec<U8Vec> = data
         .1
-        .map(|(_, v)| v)
-        .flatten()
-        .map(|(_, v)| v)
-        .flatten()
-        .map(|(_, v)| v)
-        .flatten()
-        .map(|(_, v)| v)
-        .flatten()
+        .and_then(|(_, v)| v)
+        .and_then(|(_, v)| v)
+        .and_then(|(_, v)| v)
+        .and_then(|(_, v)| v)
         .map(|(_, v)| v.iter().map(|x| x.to_vec()).collect())
         .unwrap_or_else(Vec::new);
 
     Ok((rest, samples))
 }
+
+/// Build an error for a header line which failed to parse.
+/// The context attached to the nom error is used as the reason if it exists.
+pub fn header_error(
+    line: &[u8],
+    line_num: u64,
+    error: nom::Err<nom::error::VerboseError<&[u8]>>,
+) -> VCFError {
+    let body = line.strip_suffix(b"\n").unwrap_or(line);
+    let body = body.strip_suffix(b"\r").unwrap_or(body);
+    let (rest, context) = match &error {
+        nom::Err::Error(e) | nom::Err::Failure(e) => (
+            e.errors.first().map(|x| x.0).unwrap_or(&[]),
+            e.errors.iter().find_map(|x| match x.1 {
+                nom::error::VerboseErrorKind::Context(c) => Some(c),
+                _ => None,
+            }),
+        ),
+        nom::Err::Incomplete(_) => (&[][..], None),
+    };
+    let offset = (line.len() - rest.len()).min(body.len());
+    let text = if offset < body.len() {
+        &body[offset..]
+    } else {
+        body
+    };
+
+    let (field, reason): (&[u8], _) = if let Some(content) = body.strip_prefix(b"##") {
+        let key = content.split(|x| *x == b'=').next().unwrap_or(b"");
+        let reason = match context {
+            Some(x) => x,
+            None if key.is_empty() => "Empty header line",
+            None => "Invalid header line",
+        };
+        (key, reason)
+    } else if body.starts_with(b"#CHROM") {
+        (b"#CHROM", context.unwrap_or("Invalid column header line"))
+    } else {
+        (b"", "Not a header line")
+    };
+    VCFError::HeaderParseError(ParseErrorDetail::new(
+        line_num,
+        offset + 1,
+        field,
+        text,
+        reason,
+    ))
+}
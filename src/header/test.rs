@@ -1,6 +1,7 @@
 use super::*;
+use crate::ParseErrorDetail;
 use std::collections::HashSet;
-use std::io::BufReader;
+use std::io::{BufReader, Read};
 
 #[test]
 fn test_header_item_parser() {
@@ -107,6 +108,21 @@ fn test_parse_header_entries() {
             ]
         )
     );
+
+    // Entries keep escapes, which are removed in header contents
+    assert_eq!(
+        parser::parse_header_entries(br#"ID=X,Description="A \"B\" \\ C",Source="">"#).unwrap(),
+        (
+            &b">"[..],
+            vec![
+                (&b"ID"[..], &b"X"[..]),
+                (&b"Description"[..], &br#"A \"B\" \\ C"#[..]),
+                (&b"Source"[..], &b""[..])
+            ]
+        )
+    );
+    assert_eq!(parser::unescape(br#"A \"B\" \\ C"#), br#"A "B" \ C"#);
+    assert_eq!(parser::unescape(br"C:\path\"), br"C:\path\");
 }
 
 #[test]
@@ -333,9 +349,8 @@ fn test_parse_samples() {
 fn test_parse_header() -> Result<(), VCFError> {
     let mut simple1_vcf = BufReader::new(&include_bytes!("../../testfiles/simple1.vcf")[..]);
 
-    let (line_count, unprocessed_line, header) = parse_header(&mut simple1_vcf)?;
+    let (line_count, header) = parse_header(&mut simple1_vcf)?;
     assert_eq!(line_count, 20);
-    assert_eq!(unprocessed_line, None);
 
     assert_eq!(
         header.samples,
@@ -525,7 +540,7 @@ fn test_parse_header() -> Result<(), VCFError> {
     assert_eq!(
         header
             .format_list()
-            .map(|x| -> &[u8] { &x })
+            .map(|x| -> &[u8] { x })
             .collect::<HashSet<&[u8]>>(),
         [&b"AD"[..], &b"DP"[..], &b"GT"[..]]
             .iter()
@@ -545,7 +560,7 @@ fn test_parse_header() -> Result<(), VCFError> {
     assert_eq!(
         header
             .filter_list()
-            .map(|x| -> &[u8] { &x })
+            .map(|x| -> &[u8] { x })
             .collect::<HashSet<&[u8]>>(),
         [&b"PASS"[..], &b"LowQual"[..]]
             .iter()
@@ -558,7 +573,7 @@ fn test_parse_header() -> Result<(), VCFError> {
     assert_eq!(
         header
             .alt_list()
-            .map(|x| -> &[u8] { &x })
+            .map(|x| -> &[u8] { x })
             .collect::<HashSet<&[u8]>>(),
         [&b"NON_REF"[..]]
             .iter()
@@ -575,3 +590,209 @@ fn test_parse_header() -> Result<(), VCFError> {
 
     Ok(())
 }
+
+#[test]
+fn test_parse_header_without_samples() -> Result<(), VCFError> {
+    let mut vcf_data = BufReader::new(
+        &b"##fileformat=VCFv4.2\n##contig=<ID=1,length=249250621>\n1\t100\t.\tA\tC\n"[..],
+    );
+
+    let (line_count, header) = parse_header(&mut vcf_data)?;
+    assert_eq!(line_count, 2);
+    assert_eq!(header.items().len(), 2);
+    assert_eq!(header.samples(), &[] as &[U8Vec]);
+
+    let mut rest = Vec::new();
+    vcf_data.read_to_end(&mut rest)?;
+    assert_eq!(rest, b"1\t100\t.\tA\tC\n");
+
+    let (line_count, header) = parse_header(&mut BufReader::new(&b""[..]))?;
+    assert_eq!(line_count, 0);
+    assert!(header.items().is_empty());
+
+    Ok(())
+}
+
+#[test]
+fn test_header_contig() -> Result<(), VCFError> {
+    let (_, header) = parse_header(&mut BufReader::new(
+        &b"##fileformat=VCFv4.2\n##contig=<ID=1,length=249250621>\n##contig=<ID=2>\n"[..],
+    ))?;
+    assert_eq!(
+        header.contig(b"1"),
+        Some(VCFHeaderContig {
+            id: b"1",
+            length: Some(249250621)
+        })
+    );
+    assert_eq!(
+        header.contig(b"2"),
+        Some(VCFHeaderContig {
+            id: b"2",
+            length: None
+        })
+    );
+    assert_eq!(header.contig(b"3"), None);
+    assert_eq!(header.contig_list().count(), 2);
+    Ok(())
+}
+
+#[test]
+fn test_header_parse_error() {
+    match VCFHeaderLine::from_bytes(b"##\n", 3) {
+        Err(VCFError::HeaderParseError(x)) => {
+            assert_eq!(
+                x,
+                ParseErrorDetail::new(3, 3, b"", b"##", "Empty header line")
+            )
+        }
+        _ => panic!(),
+    }
+    match VCFHeaderLine::from_bytes(b"#fileformat=VCFv4.2", 1) {
+        Err(VCFError::HeaderParseError(x)) => assert_eq!(x.reason, "Not a header line"),
+        _ => panic!(),
+    }
+
+    let mut vcf_data =
+        BufReader::new(&b"##fileformat=VCFv4.2\n#CHROM\tPOS\tID\tREF\tALT\tQUAL\tFOO\n"[..]);
+    match parse_header(&mut vcf_data) {
+        Err(VCFError::HeaderParseError(x)) => assert_eq!(
+            x,
+            ParseErrorDetail::new(2, 27, b"#CHROM", b"\tFOO", "Invalid column header line")
+        ),
+        _ => panic!(),
+    }
+}
+
+#[test]
+fn test_lint_header() -> Result<(), VCFError> {
+    let data = include_bytes!("../../testfiles/1kGP-subset.vcf");
+    let (_, header) = parse_header(&mut BufReader::new(&data[..]))?;
+    assert_eq!(lint_header(&header), vec![]);
+
+    let lines = [
+        "##INFO=<ID=DP,Number=1,Type=Integer,Description=\"Depth\">",
+        "##INFO=<ID=1X,Number=1,Type=Integer,Description=\"Valid before VCFv4.3\">",
+        "##INFO=<ID=SB,Number=1,Type=Integer,Description=\"Not reserved before VCFv4.3\">",
+        "##fileformat=VCFv4.1",
+        "##INFO=<ID=DP,Number=1,Type=Integer,Description=\"Depth\">",
+        "##INFO=<ID=AF,Number=1,Type=Float,Description=\"Allele frequency\">",
+        "##INFO=<ID=RC,Number=R,Type=Integer,Description=\"Read count\">",
+        "##INFO=<ID=XF,Number=1,Type=Flag,Description=\"Flag\">",
+        "##FORMAT=<ID=GQ,Number=1,Type=Float,Description=\"Genotype quality\">",
+        "##FORMAT=<ID=FL,Number=0,Type=Flag,Description=\"Flag\">",
+        "##contig=<length=100>",
+    ];
+    let items = lines
+        .iter()
+        .map(|x| x.parse())
+        .collect::<Result<Vec<VCFHeaderLine>, _>>()?;
+    let header = VCFHeader::new(items.clone(), vec![]);
+    assert_eq!(
+        header
+            .lint()
+            .iter()
+            .map(|x| (x.line.clone(), x.reason))
+            .collect::<Vec<_>>(),
+        vec![
+            (Some(items[3].clone()), "fileformat is not the first line"),
+            (Some(items[4].clone()), "Duplicated INFO ID"),
+            (Some(items[5].clone()), "Reserved key has wrong Number"),
+            (
+                Some(items[6].clone()),
+                "Number=R is not supported before VCFv4.2"
+            ),
+            (Some(items[7].clone()), "Flag must have Number=0"),
+            (Some(items[8].clone()), "Reserved key has wrong Type"),
+            (Some(items[9].clone()), "FORMAT cannot be Flag"),
+            (Some(items[10].clone()), "Required field is missing"),
+        ]
+    );
+
+    let items = vec![
+        "##INFO=<ID=1X,Number=1,Type=Integer,Description=\"Invalid\">".parse()?,
+        "##INFO=<ID=1000G,Number=0,Type=Flag,Description=\"1000 Genomes\">".parse()?,
+    ];
+    let violations = lint_header(&VCFHeader::new(items, vec![]));
+    assert_eq!(
+        violations.iter().map(|x| x.to_string()).collect::<Vec<_>>(),
+        vec![
+            "Invalid ID: ##INFO=<ID=1X,Number=1,Type=Integer,Description=\"Invalid\">",
+            "No fileformat line"
+        ]
+    );
+    Ok(())
+}
+
+#[test]
+fn test_header_builder() -> Result<(), VCFError> {
+    let header = VCFHeaderBuilder::new(VCFVersion::Vcf4_3)
+        .contig(b"1", Some(249250621))
+        .contig(b"MT", None)
+        .info(b"AC", Number::Allele, ValueType::Integer, b"Allele count")
+        .info(
+            b"DB",
+            Number::Zero,
+            ValueType::Flag,
+            b"dbSNP \"membership\"",
+        )
+        .format(
+            b"PL",
+            Number::Genotype,
+            ValueType::Integer,
+            b"Likelihoods, phred-scaled",
+        )
+        .filter(b"q10", b"Quality below 10")
+        .alt(b"DEL", b"Deletion")
+        .other(b"source", b"vcf-rs")
+        .samples(&[b"S1", b"S2"])
+        .build()?;
+
+    let mut data = Vec::new();
+    crate::VCFWriter::new(&mut data, &header)?;
+    assert_eq!(
+        str::from_utf8(&data)?,
+        r#"##fileformat=VCFv4.3
+##contig=<ID=1,length=249250621>
+##contig=<ID=MT>
+##INFO=<ID=AC,Number=A,Type=Integer,Description="Allele count">
+##INFO=<ID=DB,Number=0,Type=Flag,Description="dbSNP \"membership\"">
+##FORMAT=<ID=PL,Number=G,Type=Integer,Description="Likelihoods, phred-scaled">
+##FILTER=<ID=q10,Description="Quality below 10">
+##ALT=<ID=DEL,Description="Deletion">
+##source=vcf-rs
+#CHROM	POS	ID	REF	ALT	QUAL	FILTER	INFO	FORMAT	S1	S2
+"#
+    );
+
+    let (_, parsed) = parse_header(&mut BufReader::new(&data[..]))?;
+    assert_eq!(parsed, header);
+    assert_eq!(lint_header(&header), vec![]);
+    assert_eq!(
+        header.format(b"PL").unwrap().description,
+        b"Likelihoods, phred-scaled"
+    );
+    assert_eq!(
+        header.info(b"DB").unwrap().description,
+        br#"dbSNP "membership""#
+    );
+    assert_eq!(
+        parsed.info(b"DB").unwrap().description,
+        br#"dbSNP "membership""#
+    );
+    assert_eq!(header.contig(b"MT").unwrap().length, None);
+
+    assert!(matches!(
+        VCFHeaderBuilder::new(VCFVersion::Vcf4_2)
+            .info(b"A,B", Number::Number(1), ValueType::Integer, b"Invalid")
+            .build(),
+        Err(VCFError::InvalidHeaderValue(x, "Forbidden character")) if x == b"A,B"
+    ));
+    assert!(matches!(
+        VCFHeaderBuilder::new(VCFVersion::Vcf4_2)
+            .sample(b"")
+            .build(),
+        Err(VCFError::InvalidHeaderValue(_, "Empty value"))
+    ));
+    Ok(())
+}
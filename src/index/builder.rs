@@ -0,0 +1,187 @@
+use super::{reg2bin, Chunk, ReferenceIndex};
+use crate::bgzf::VirtualOffset;
+use crate::{U8Vec, VCFError};
+use std::collections::HashMap;
+
+/// Collect bins and linear index from sorted records.
+#[derive(Debug, Clone)]
+pub(crate) struct IndexBuilder {
+    min_shift: u32,
+    depth: u32,
+    names: Vec<U8Vec>,
+    name_to_index: HashMap<U8Vec, usize>,
+    references: Vec<ReferenceIndex>,
+    current: Option<CurrentReference>,
+}
+
+#[derive(Debug, Clone)]
+struct CurrentReference {
+    index: usize,
+    last_begin: u64,
+    bin: u32,
+    chunk_begin: VirtualOffset,
+    first_offset: VirtualOffset,
+    last_offset: VirtualOffset,
+    record_count: u64,
+}
+
+impl IndexBuilder {
+    pub fn new(min_shift: u32, depth: u32) -> Self {
+        IndexBuilder {
+            min_shift,
+            depth,
+            names: Vec::new(),
+            name_to_index: HashMap::new(),
+            references: Vec::new(),
+            current: None,
+        }
+    }
+
+    /// Check that a record which covers 0-based, half-open region can be added
+    /// after the records pushed so far.
+    pub fn check(&self, chromosome: &[u8], begin: u64, end: u64) -> Result<(), VCFError> {
+        let end = end.max(begin + 1);
+        if end > 1 << (self.min_shift + self.depth * 3) {
+            return Err(VCFError::OutOfIndexRange(chromosome.to_vec(), end));
+        }
+        match self.current.as_ref() {
+            Some(x) if self.names[x.index] == chromosome => {
+                if begin < x.last_begin {
+                    return Err(VCFError::UnsortedRecord(chromosome.to_vec(), begin + 1));
+                }
+            }
+            _ => {
+                if self.name_to_index.contains_key(chromosome) {
+                    return Err(VCFError::UnsortedRecord(chromosome.to_vec(), begin + 1));
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Add a record which covers 0-based, half-open region and is stored
+    /// between `begin_offset` and `end_offset`.
+    pub fn push(
+        &mut self,
+        chromosome: &[u8],
+        begin: u64,
+        end: u64,
+        begin_offset: VirtualOffset,
+        end_offset: VirtualOffset,
+    ) -> Result<(), VCFError> {
+        self.check(chromosome, begin, end)?;
+        let end = end.max(begin + 1);
+        let bin = reg2bin(begin, end, self.min_shift, self.depth);
+
+        let same_reference = self
+            .current
+            .as_ref()
+            .map(|x| self.names[x.index] == chromosome)
+            .unwrap_or(false);
+        if same_reference {
+            let current = self.current.as_mut().unwrap();
+            if current.bin != bin {
+                let chunk = Chunk {
+                    begin: current.chunk_begin,
+                    end: begin_offset,
+                };
+                add_chunk(&mut self.references[current.index], current.bin, chunk);
+                current.bin = bin;
+                current.chunk_begin = begin_offset;
+            }
+        } else {
+            self.finish_reference();
+            self.names.push(chromosome.to_vec());
+            self.name_to_index
+                .insert(chromosome.to_vec(), self.names.len() - 1);
+            self.references.push(ReferenceIndex::default());
+            self.current = Some(CurrentReference {
+                index: self.names.len() - 1,
+                last_begin: begin,
+                bin,
+                chunk_begin: begin_offset,
+                first_offset: begin_offset,
+                last_offset: end_offset,
+                record_count: 0,
+            });
+        }
+
+        let current = self.current.as_mut().unwrap();
+        current.last_begin = begin;
+        current.last_offset = end_offset;
+        current.record_count += 1;
+
+        let intervals = &mut self.references[current.index].intervals;
+        let last_window = ((end - 1) >> self.min_shift) as usize;
+        if intervals.len() <= last_window {
+            intervals.resize(last_window + 1, VirtualOffset(u64::MAX));
+        }
+        for one in intervals[(begin >> self.min_shift) as usize..=last_window].iter_mut() {
+            if one.0 == u64::MAX {
+                *one = begin_offset;
+            }
+        }
+
+        Ok(())
+    }
+
+    fn finish_reference(&mut self) {
+        if let Some(current) = self.current.take() {
+            let reference = &mut self.references[current.index];
+            add_chunk(
+                reference,
+                current.bin,
+                Chunk {
+                    begin: current.chunk_begin,
+                    end: current.last_offset,
+                },
+            );
+            reference.bins.insert(
+                meta_bin(self.depth),
+                vec![
+                    Chunk {
+                        begin: current.first_offset,
+                        end: current.last_offset,
+                    },
+                    Chunk {
+                        begin: VirtualOffset(current.record_count),
+                        end: VirtualOffset(0),
+                    },
+                ],
+            );
+
+            let mut previous = reference
+                .intervals
+                .iter()
+                .find(|x| x.0 != u64::MAX)
+                .copied()
+                .unwrap_or_default();
+            for one in reference.intervals.iter_mut() {
+                if one.0 == u64::MAX {
+                    *one = previous;
+                } else {
+                    previous = *one;
+                }
+            }
+        }
+    }
+
+    /// Finish building and return sequence names and indexes for each sequence.
+    pub fn finish(mut self) -> (Vec<U8Vec>, Vec<ReferenceIndex>) {
+        self.finish_reference();
+        (self.names, self.references)
+    }
+}
+
+/// Bin number to store metadata.
+pub(crate) fn meta_bin(depth: u32) -> u32 {
+    super::first_bin(depth + 1) + 1
+}
+
+fn add_chunk(reference: &mut ReferenceIndex, bin: u32, chunk: Chunk) {
+    let chunks = reference.bins.entry(bin).or_default();
+    match chunks.last_mut() {
+        Some(last) if last.end == chunk.begin => last.end = chunk.end,
+        _ => chunks.push(chunk),
+    }
+}
@@ -0,0 +1,225 @@
+use super::{
+    first_bin, initial_capacity, read_bytes, read_count, read_i32, read_u32, read_u64,
+    valid_csi_parameters, write_i32, write_u32, write_u64, Chunk, ReferenceIndex,
+};
+use crate::bgzf::{BGZFReader, BGZFWriter, VirtualOffset};
+use crate::{U8Vec, VCFError};
+use std::collections::HashMap;
+use std::convert::TryFrom;
+use std::io::{self, Read, Write};
+
+/// Size of tabix compatible header in auxiliary data, without sequence names.
+const AUX_HEADER_SIZE: usize = 28;
+
+/// Coordinate-sorted index (`.csi`).
+///
+/// Unlike tabix, the size of the smallest bin and the number of levels are
+/// configurable, so positions beyond 2^29 can be indexed.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CSIIndex {
+    min_shift: u32,
+    depth: u32,
+    aux: U8Vec,
+    names: Vec<U8Vec>,
+    name_to_index: HashMap<U8Vec, usize>,
+    pub(super) references: Vec<ReferenceIndex>,
+    unplaced_count: Option<u64>,
+}
+
+impl CSIIndex {
+    /// Create CSI index for VCF. Tabix compatible header is stored as auxiliary data.
+    pub(crate) fn new(
+        names: Vec<U8Vec>,
+        mut references: Vec<ReferenceIndex>,
+        min_shift: u32,
+        depth: u32,
+    ) -> Self {
+        for one in references.iter_mut() {
+            one.intervals_to_bin_offsets(depth);
+        }
+
+        let names_length: usize = names.iter().map(|x| x.len() + 1).sum();
+        let mut aux = Vec::with_capacity(AUX_HEADER_SIZE + names_length);
+        for one in [2, 1, 2, 0, i32::from(b'#'), 0, names_length as i32].iter() {
+            aux.extend_from_slice(&one.to_le_bytes());
+        }
+        for one in names.iter() {
+            aux.extend_from_slice(one);
+            aux.push(0);
+        }
+
+        CSIIndex {
+            min_shift,
+            depth,
+            aux,
+            name_to_index: create_name_to_index(&names),
+            names,
+            references,
+            unplaced_count: Some(0),
+        }
+    }
+
+    /// Load BGZF compressed CSI index.
+    pub fn from_reader<R: Read>(reader: R) -> Result<Self, VCFError> {
+        let mut reader = BGZFReader::new(reader);
+        let mut magic = [0u8; 4];
+        reader.read_exact(&mut magic)?;
+        if &magic != b"CSI\x01" {
+            return Err(VCFError::IndexParseError("Not a CSI index"));
+        }
+        CSIIndex::read_contents(&mut reader)
+    }
+
+    /// Load uncompressed CSI index after the magic.
+    pub(crate) fn read_contents<R: Read>(reader: &mut R) -> Result<Self, VCFError> {
+        let min_shift = read_i32(reader)?;
+        let depth = read_i32(reader)?;
+        if min_shift < 0 || depth < 0 || !valid_csi_parameters(min_shift as u32, depth as u32) {
+            return Err(VCFError::IndexParseError("Invalid min_shift or depth"));
+        }
+        let aux_length = read_count(reader)?;
+        let aux = read_bytes(reader, aux_length)?;
+        let names = parse_aux_names(&aux)?;
+
+        let reference_count = read_count(reader)?;
+        if !names.is_empty() && names.len() != reference_count {
+            return Err(VCFError::IndexParseError("Wrong number of sequence names"));
+        }
+        let mut references = Vec::with_capacity(initial_capacity(reference_count));
+        for _ in 0..reference_count {
+            let bin_count = read_count(reader)?;
+            let mut reference = ReferenceIndex::default();
+            for _ in 0..bin_count {
+                let bin = read_u32(reader)?;
+                let bin_offset = VirtualOffset(read_u64(reader)?);
+                let chunk_count = read_count(reader)?;
+                let mut chunks = Vec::with_capacity(initial_capacity(chunk_count));
+                for _ in 0..chunk_count {
+                    chunks.push(Chunk {
+                        begin: VirtualOffset(read_u64(reader)?),
+                        end: VirtualOffset(read_u64(reader)?),
+                    });
+                }
+                reference.bins.insert(bin, chunks);
+                // Offset of the metadata pseudo-bin is meaningless
+                if bin < first_bin(depth as u32 + 1) {
+                    reference.bin_offsets.insert(bin, bin_offset);
+                }
+            }
+            references.push(reference);
+        }
+
+        let unplaced_count = match read_u64(reader) {
+            Ok(x) => Some(x),
+            Err(e) if e.kind() == io::ErrorKind::UnexpectedEof => None,
+            Err(e) => return Err(e.into()),
+        };
+
+        Ok(CSIIndex {
+            min_shift: min_shift as u32,
+            depth: depth as u32,
+            aux,
+            name_to_index: create_name_to_index(&names),
+            names,
+            references,
+            unplaced_count,
+        })
+    }
+
+    /// Write BGZF compressed CSI index.
+    pub fn write<W: Write>(&self, writer: W) -> Result<(), VCFError> {
+        let mut writer = BGZFWriter::new(writer);
+        writer.write_all(b"CSI\x01")?;
+        write_i32(&mut writer, self.min_shift as i32)?;
+        write_i32(&mut writer, self.depth as i32)?;
+        write_i32(&mut writer, self.aux.len() as i32)?;
+        writer.write_all(&self.aux)?;
+        write_i32(&mut writer, self.references.len() as i32)?;
+
+        for reference in self.references.iter() {
+            let mut bins: Vec<_> = reference.bins.iter().collect();
+            bins.sort();
+            write_i32(&mut writer, bins.len() as i32)?;
+            for (bin, chunks) in bins {
+                write_u32(&mut writer, *bin)?;
+                write_u64(
+                    &mut writer,
+                    reference
+                        .bin_offsets
+                        .get(bin)
+                        .copied()
+                        .unwrap_or_default()
+                        .0,
+                )?;
+                write_i32(&mut writer, chunks.len() as i32)?;
+                for one in chunks {
+                    write_u64(&mut writer, one.begin.0)?;
+                    write_u64(&mut writer, one.end.0)?;
+                }
+            }
+        }
+
+        if let Some(unplaced_count) = self.unplaced_count {
+            write_u64(&mut writer, unplaced_count)?;
+        }
+        writer.finish()?;
+        Ok(())
+    }
+
+    /// Sequence names in the auxiliary data.
+    /// Empty if the index does not have tabix compatible header, such as BCF index.
+    pub fn names(&self) -> &[U8Vec] {
+        &self.names
+    }
+
+    /// Bit width of the smallest bin.
+    pub fn min_shift(&self) -> u32 {
+        self.min_shift
+    }
+
+    /// Number of levels in the binning index.
+    pub fn depth(&self) -> u32 {
+        self.depth
+    }
+
+    pub fn aux(&self) -> &[u8] {
+        &self.aux
+    }
+
+    /// Number of records without coordinates, if recorded.
+    pub fn unplaced_count(&self) -> Option<u64> {
+        self.unplaced_count
+    }
+
+    /// Chunks which may contain records overlapping with 0-based, half-open region.
+    pub(crate) fn chunks(&self, chromosome: &[u8], begin: u64, end: u64) -> Vec<Chunk> {
+        match self.name_to_index.get(chromosome) {
+            Some(x) => self.references[*x].chunks(begin, end, self.min_shift, self.depth),
+            None => Vec::new(),
+        }
+    }
+}
+
+fn parse_aux_names(aux: &[u8]) -> Result<Vec<U8Vec>, VCFError> {
+    if aux.len() < AUX_HEADER_SIZE {
+        return Ok(Vec::new());
+    }
+    let names_length = i32::from_le_bytes([aux[24], aux[25], aux[26], aux[27]]);
+    let names_data = usize::try_from(names_length)
+        .ok()
+        .and_then(|x| aux.get(AUX_HEADER_SIZE..AUX_HEADER_SIZE + x))
+        .ok_or(VCFError::IndexParseError("Invalid sequence names"))?;
+    Ok(names_data
+        .split(|x| *x == 0)
+        .filter(|x| !x.is_empty())
+        .map(|x| x.to_vec())
+        .collect())
+}
+
+fn create_name_to_index(names: &[U8Vec]) -> HashMap<U8Vec, usize> {
+    names
+        .iter()
+        .enumerate()
+        .map(|(k, v)| (v.to_vec(), k))
+        .collect()
+}
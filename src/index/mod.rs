@@ -0,0 +1,446 @@
+mod builder;
+mod csi;
+mod tabix;
+
+use crate::bgzf::{BGZFReader, BGZFWriter, VirtualOffset};
+use crate::{U8Vec, VCFError, VCFHeader, VCFRecord, VCFWriter};
+use builder::IndexBuilder;
+use std::collections::HashMap;
+use std::io::{self, BufRead, Read, Seek, Write};
+use std::str;
+
+pub use csi::CSIIndex;
+pub use tabix::TabixIndex;
+
+/// Tabix or CSI index.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum VCFIndex {
+    Tabix(TabixIndex),
+    CSI(CSIIndex),
+}
+
+impl VCFIndex {
+    /// Load BGZF compressed tabix or CSI index. Format is detected from the magic.
+    pub fn from_reader<R: Read>(reader: R) -> Result<Self, VCFError> {
+        let mut reader = BGZFReader::new(reader);
+        let mut magic = [0u8; 4];
+        reader.read_exact(&mut magic)?;
+        match &magic {
+            b"TBI\x01" => Ok(VCFIndex::Tabix(TabixIndex::read_contents(&mut reader)?)),
+            b"CSI\x01" => Ok(VCFIndex::CSI(CSIIndex::read_contents(&mut reader)?)),
+            _ => Err(VCFError::IndexParseError("Unknown index format")),
+        }
+    }
+
+    /// Write BGZF compressed index.
+    pub fn write<W: Write>(&self, writer: W) -> Result<(), VCFError> {
+        match self {
+            VCFIndex::Tabix(x) => x.write(writer),
+            VCFIndex::CSI(x) => x.write(writer),
+        }
+    }
+
+    pub fn names(&self) -> &[U8Vec] {
+        match self {
+            VCFIndex::Tabix(x) => x.names(),
+            VCFIndex::CSI(x) => x.names(),
+        }
+    }
+
+    /// Chunks which may contain records overlapping with 0-based, half-open region.
+    pub(crate) fn chunks(&self, chromosome: &[u8], begin: u64, end: u64) -> Vec<Chunk> {
+        match self {
+            VCFIndex::Tabix(x) => x.chunks(chromosome, begin, end),
+            VCFIndex::CSI(x) => x.chunks(chromosome, begin, end),
+        }
+    }
+}
+
+impl From<TabixIndex> for VCFIndex {
+    fn from(index: TabixIndex) -> Self {
+        VCFIndex::Tabix(index)
+    }
+}
+
+impl From<CSIIndex> for VCFIndex {
+    fn from(index: CSIIndex) -> Self {
+        VCFIndex::CSI(index)
+    }
+}
+
+/// A range of virtual offsets in a BGZF file.
+#[derive(Debug, Clone, Copy, Hash, PartialEq, Eq, PartialOrd, Ord)]
+pub(crate) struct Chunk {
+    pub begin: VirtualOffset,
+    pub end: VirtualOffset,
+}
+
+/// Binning and linear index of one sequence.
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub(crate) struct ReferenceIndex {
+    pub bins: HashMap<u32, Vec<Chunk>>,
+    /// Linear index. Empty for CSI.
+    pub intervals: Vec<VirtualOffset>,
+    /// Smallest offset of records in each bin. Used by CSI instead of linear index.
+    pub bin_offsets: HashMap<u32, VirtualOffset>,
+}
+
+impl ReferenceIndex {
+    /// Chunks which may contain records overlapping with 0-based, half-open region.
+    /// The region is clamped to the range of the index, same as htslib.
+    pub fn chunks(&self, begin: u64, end: u64, min_shift: u32, depth: u32) -> Vec<Chunk> {
+        let max_position = 1u64 << (min_shift + depth * 3);
+        if begin >= max_position {
+            return Vec::new();
+        }
+        let end = end.min(max_position);
+        let min_offset = self.min_offset(begin, min_shift, depth);
+        let chunks = reg2bins(begin, end, min_shift, depth)
+            .into_iter()
+            .filter_map(|x| self.bins.get(&x))
+            .flatten()
+            .filter(|x| x.end > min_offset)
+            .copied()
+            .collect();
+        merge_chunks(chunks)
+    }
+
+    /// Records starting at or after `begin` are not found before this offset.
+    fn min_offset(&self, begin: u64, min_shift: u32, depth: u32) -> VirtualOffset {
+        if !self.intervals.is_empty() {
+            let i = ((begin >> min_shift) as usize).min(self.intervals.len() - 1);
+            return self.intervals[i];
+        }
+        let mut bin = first_bin(depth) + (begin >> min_shift) as u32;
+        loop {
+            if let Some(x) = self.bin_offsets.get(&bin) {
+                return *x;
+            }
+            if bin == 0 {
+                return VirtualOffset(0);
+            }
+            bin = (bin - 1) >> 3;
+        }
+    }
+
+    /// Replace linear index with offsets of each bin, same as htslib does for CSI.
+    pub fn intervals_to_bin_offsets(&mut self, depth: u32) {
+        let bin_limit = first_bin(depth + 1);
+        for bin in self.bins.keys() {
+            if *bin >= bin_limit {
+                continue;
+            }
+            let level = (0..=depth).rev().find(|x| first_bin(*x) <= *bin).unwrap();
+            let window = ((bin - first_bin(level)) << ((depth - level) * 3)) as usize;
+            let offset = self.intervals.get(window).copied().unwrap_or_default();
+            self.bin_offsets.insert(*bin, offset);
+        }
+        self.intervals.clear();
+    }
+}
+
+/// First bin number in the level.
+/// Computed in u64 because `level` is up to 11 for the metadata bin of depth 10.
+pub(crate) fn first_bin(level: u32) -> u32 {
+    (((1u64 << (level * 3)) - 1) / 7) as u32
+}
+
+/// Check `min_shift` and `depth` of CSI index.
+pub(crate) fn valid_csi_parameters(min_shift: u32, depth: u32) -> bool {
+    depth <= 10 && min_shift < 64 && min_shift + depth * 3 < 64
+}
+
+/// List bins which may overlap with 0-based, half-open region.
+pub(crate) fn reg2bins(begin: u64, end: u64, min_shift: u32, depth: u32) -> Vec<u32> {
+    let end = end.max(begin + 1) - 1;
+    let mut bins = Vec::new();
+    let mut shift = min_shift + depth * 3;
+    for level in 0..=depth {
+        for i in (begin >> shift)..=(end >> shift) {
+            bins.push(first_bin(level) + i as u32);
+        }
+        shift -= 3;
+    }
+    bins
+}
+
+/// Smallest bin which contains 0-based, half-open region.
+pub(crate) fn reg2bin(begin: u64, end: u64, min_shift: u32, depth: u32) -> u32 {
+    let end = end.max(begin + 1) - 1;
+    let mut shift = min_shift;
+    for level in (1..=depth).rev() {
+        if begin >> shift == end >> shift {
+            return first_bin(level) + (begin >> shift) as u32;
+        }
+        shift += 3;
+    }
+    0
+}
+
+/// Sort chunks and merge overlapping ones.
+pub(crate) fn merge_chunks(mut chunks: Vec<Chunk>) -> Vec<Chunk> {
+    chunks.sort();
+    let mut merged: Vec<Chunk> = Vec::with_capacity(chunks.len());
+    for one in chunks {
+        match merged.last_mut() {
+            Some(last) if one.begin <= last.end => last.end = last.end.max(one.end),
+            _ => merged.push(one),
+        }
+    }
+    merged
+}
+
+/// Last position (1-based, inclusive) covered by the record.
+pub(crate) fn record_end(record: &VCFRecord) -> u64 {
+    record
+        .info(b"END")
+        .and_then(|x| x.first())
+        .and_then(|x| str::from_utf8(x).ok())
+        .and_then(|x| x.parse().ok())
+        .unwrap_or_else(|| record.position + record.reference.len().max(1) as u64 - 1)
+}
+
+/// Upper limit of sequence names and auxiliary data in an index.
+const MAX_INDEX_DATA_LENGTH: usize = 1 << 30;
+
+/// Read `length` bytes without allocating them in advance,
+/// because `length` comes from a possibly broken file.
+pub(crate) fn read_bytes<R: Read>(reader: &mut R, length: usize) -> Result<Vec<u8>, VCFError> {
+    if length > MAX_INDEX_DATA_LENGTH {
+        return Err(VCFError::IndexParseError("Too long data"));
+    }
+    let mut data = Vec::new();
+    reader.take(length as u64).read_to_end(&mut data)?;
+    if data.len() != length {
+        return Err(io::Error::from(io::ErrorKind::UnexpectedEof).into());
+    }
+    Ok(data)
+}
+
+/// Capacity to reserve for `count` items read from a file.
+pub(crate) fn initial_capacity(count: usize) -> usize {
+    count.min(1024)
+}
+
+pub(crate) fn read_count<R: Read>(reader: &mut R) -> Result<usize, VCFError> {
+    let count = read_i32(reader)?;
+    if count < 0 {
+        return Err(VCFError::IndexParseError("Negative count"));
+    }
+    Ok(count as usize)
+}
+
+pub(crate) fn read_i32<R: Read>(reader: &mut R) -> io::Result<i32> {
+    let mut data = [0u8; 4];
+    reader.read_exact(&mut data)?;
+    Ok(i32::from_le_bytes(data))
+}
+
+pub(crate) fn read_u32<R: Read>(reader: &mut R) -> io::Result<u32> {
+    let mut data = [0u8; 4];
+    reader.read_exact(&mut data)?;
+    Ok(u32::from_le_bytes(data))
+}
+
+pub(crate) fn read_u64<R: Read>(reader: &mut R) -> io::Result<u64> {
+    let mut data = [0u8; 8];
+    reader.read_exact(&mut data)?;
+    Ok(u64::from_le_bytes(data))
+}
+
+pub(crate) fn write_i32<W: Write>(writer: &mut W, value: i32) -> io::Result<()> {
+    writer.write_all(&value.to_le_bytes())
+}
+
+pub(crate) fn write_u32<W: Write>(writer: &mut W, value: u32) -> io::Result<()> {
+    writer.write_all(&value.to_le_bytes())
+}
+
+pub(crate) fn write_u64<W: Write>(writer: &mut W, value: u64) -> io::Result<()> {
+    writer.write_all(&value.to_le_bytes())
+}
+
+/// VCF reader for BGZF compressed and tabix or CSI indexed file.
+pub struct IndexedVCFReader<R: Read + Seek> {
+    buffer: Vec<u8>,
+    reader: BGZFReader<R>,
+    index: VCFIndex,
+    vcf_header: VCFHeader,
+    region: Option<(U8Vec, u64, u64)>,
+    chunks: Vec<Chunk>,
+    current_chunk: usize,
+}
+
+impl<R: Read + Seek> IndexedVCFReader<R> {
+    pub fn new(reader: R, index: impl Into<VCFIndex>) -> Result<Self, VCFError> {
+        let mut reader = BGZFReader::new(reader);
+        let (_, vcf_header) = crate::header::parse_header(&mut reader)?;
+        Ok(IndexedVCFReader {
+            buffer: Vec::new(),
+            reader,
+            index: index.into(),
+            vcf_header,
+            region: None,
+            chunks: Vec::new(),
+            current_chunk: 0,
+        })
+    }
+
+    /// Set region to read.
+    /// `start` and `end` are 1-based and inclusive, same as VCF positions.
+    /// Unknown chromosome results in no records.
+    pub fn fetch(&mut self, chromosome: &[u8], start: u64, end: u64) -> Result<(), VCFError> {
+        self.chunks = self.index.chunks(chromosome, start.saturating_sub(1), end);
+        self.current_chunk = 0;
+        self.region = Some((chromosome.to_vec(), start, end));
+        if let Some(first) = self.chunks.first() {
+            self.reader.seek(first.begin)?;
+        }
+        Ok(())
+    }
+
+    /// Read next record overlapping with the region.
+    /// Return false if no record was remained.
+    pub fn next_record(&mut self, record: &mut VCFRecord) -> Result<bool, VCFError> {
+        let (chromosome, start, end) = match self.region.as_ref() {
+            Some((chromosome, start, end)) => (chromosome, *start, *end),
+            None => return Ok(false),
+        };
+
+        while let Some(chunk) = self.chunks.get(self.current_chunk) {
+            let offset = self.reader.virtual_offset();
+            if offset >= chunk.end {
+                self.current_chunk += 1;
+                if let Some(next) = self.chunks.get(self.current_chunk) {
+                    if offset < next.begin {
+                        self.reader.seek(next.begin)?;
+                    }
+                }
+                continue;
+            }
+
+            self.buffer.clear();
+            if self.reader.read_until(b'\n', &mut self.buffer)? == 0 {
+                break;
+            }
+            if self.buffer.starts_with(b"#") {
+                continue;
+            }
+            // Line numbers are unknown after seeking
+            record
+                .parse_bytes(&self.buffer, 0)
+                .map_err(VCFError::without_line)?;
+            if record.chromosome != *chromosome {
+                continue;
+            }
+            if record.position > end {
+                break;
+            }
+            if record_end(record) >= start {
+                return Ok(true);
+            }
+        }
+
+        self.current_chunk = self.chunks.len();
+        Ok(false)
+    }
+
+    pub fn header(&self) -> &VCFHeader {
+        &self.vcf_header
+    }
+
+    pub fn index(&self) -> &VCFIndex {
+        &self.index
+    }
+
+    pub fn empty_record(&self) -> VCFRecord {
+        VCFRecord::new(self.vcf_header.clone())
+    }
+}
+
+/// VCF writer which writes BGZF compressed records and builds tabix or CSI index.
+/// Records must be sorted by position and grouped by chromosome.
+pub struct IndexedVCFWriter<W: Write> {
+    writer: VCFWriter<BGZFWriter<W>>,
+    builder: IndexBuilder,
+    csi: Option<(u32, u32)>,
+}
+
+impl<W: Write> IndexedVCFWriter<W> {
+    /// Create writer which builds tabix index.
+    pub fn new(writer: W, header: &VCFHeader) -> Result<Self, VCFError> {
+        IndexedVCFWriter::with_builder(
+            writer,
+            header,
+            IndexBuilder::new(tabix::TABIX_MIN_SHIFT, tabix::TABIX_DEPTH),
+            None,
+        )
+    }
+
+    /// Create writer which builds CSI index.
+    /// Positions up to 2^(min_shift + depth * 3) can be indexed.
+    /// bcftools uses 14 and 5 by default.
+    pub fn new_csi(
+        writer: W,
+        header: &VCFHeader,
+        min_shift: u32,
+        depth: u32,
+    ) -> Result<Self, VCFError> {
+        if !valid_csi_parameters(min_shift, depth) {
+            return Err(VCFError::InvalidArgument("Invalid min_shift or depth"));
+        }
+        IndexedVCFWriter::with_builder(
+            writer,
+            header,
+            IndexBuilder::new(min_shift, depth),
+            Some((min_shift, depth)),
+        )
+    }
+
+    fn with_builder(
+        writer: W,
+        header: &VCFHeader,
+        builder: IndexBuilder,
+        csi: Option<(u32, u32)>,
+    ) -> Result<Self, VCFError> {
+        let mut writer = VCFWriter::new(BGZFWriter::new(writer), header)?;
+        // Start records from a new block, same as bcftools
+        writer.writer.flush()?;
+        Ok(IndexedVCFWriter {
+            writer,
+            builder,
+            csi,
+        })
+    }
+
+    /// Write a record and add it to the index.
+    /// Unsorted or out of range records are rejected before they are written.
+    pub fn write_record(&mut self, vcf_record: &VCFRecord) -> Result<(), VCFError> {
+        let begin = vcf_record.position.saturating_sub(1);
+        let end = record_end(vcf_record);
+        self.builder.check(&vcf_record.chromosome, begin, end)?;
+        let begin_offset = self.writer.virtual_offset();
+        self.writer.write_record(vcf_record)?;
+        self.builder.push(
+            &vcf_record.chromosome,
+            begin,
+            end,
+            begin_offset,
+            self.writer.virtual_offset(),
+        )
+    }
+
+    /// Write BGZF EOF marker, then return the inner writer and the index.
+    pub fn finish(self) -> Result<(W, VCFIndex), VCFError> {
+        let writer = self.writer.finish()?;
+        let (names, references) = self.builder.finish();
+        let index = match self.csi {
+            Some((min_shift, depth)) => CSIIndex::new(names, references, min_shift, depth).into(),
+            None => TabixIndex::new(names, references).into(),
+        };
+        Ok((writer, index))
+    }
+}
+
+#[cfg(test)]
+mod test;
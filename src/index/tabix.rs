@@ -0,0 +1,218 @@
+use super::{
+    initial_capacity, read_bytes, read_count, read_i32, read_u32, read_u64, write_i32, write_u32,
+    write_u64, Chunk, ReferenceIndex,
+};
+use crate::bgzf::{BGZFReader, BGZFWriter, VirtualOffset};
+use crate::{U8Vec, VCFError};
+use std::collections::HashMap;
+use std::io::{self, Read, Write};
+
+pub const TABIX_MIN_SHIFT: u32 = 14;
+pub const TABIX_DEPTH: u32 = 5;
+
+/// Tabix index (`.tbi`).
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TabixIndex {
+    format: i32,
+    column_sequence: i32,
+    column_begin: i32,
+    column_end: i32,
+    meta: u8,
+    skip: i32,
+    names: Vec<U8Vec>,
+    name_to_index: HashMap<U8Vec, usize>,
+    pub(super) references: Vec<ReferenceIndex>,
+    unplaced_count: Option<u64>,
+}
+
+impl TabixIndex {
+    /// Create tabix index for VCF.
+    pub(crate) fn new(names: Vec<U8Vec>, references: Vec<ReferenceIndex>) -> Self {
+        TabixIndex {
+            format: 2,
+            column_sequence: 1,
+            column_begin: 2,
+            column_end: 0,
+            meta: b'#',
+            skip: 0,
+            name_to_index: names
+                .iter()
+                .enumerate()
+                .map(|(k, v)| (v.to_vec(), k))
+                .collect(),
+            names,
+            references,
+            unplaced_count: Some(0),
+        }
+    }
+
+    /// Load BGZF compressed tabix index.
+    pub fn from_reader<R: Read>(reader: R) -> Result<Self, VCFError> {
+        let mut reader = BGZFReader::new(reader);
+        let mut magic = [0u8; 4];
+        reader.read_exact(&mut magic)?;
+        if &magic != b"TBI\x01" {
+            return Err(VCFError::IndexParseError("Not a tabix index"));
+        }
+        TabixIndex::read_contents(&mut reader)
+    }
+
+    /// Load uncompressed tabix index after the magic.
+    pub(crate) fn read_contents<R: Read>(reader: &mut R) -> Result<Self, VCFError> {
+        let reference_count = read_count(reader)?;
+        let format = read_i32(reader)?;
+        let column_sequence = read_i32(reader)?;
+        let column_begin = read_i32(reader)?;
+        let column_end = read_i32(reader)?;
+        let meta = read_i32(reader)? as u8;
+        let skip = read_i32(reader)?;
+        let names_length = read_count(reader)?;
+        let names_data = read_bytes(reader, names_length)?;
+        let names: Vec<U8Vec> = names_data
+            .split(|x| *x == 0)
+            .take(reference_count)
+            .map(|x| x.to_vec())
+            .collect();
+        if names.len() != reference_count {
+            return Err(VCFError::IndexParseError("Wrong number of sequence names"));
+        }
+
+        let mut references = Vec::with_capacity(initial_capacity(reference_count));
+        for _ in 0..reference_count {
+            let bin_count = read_count(reader)?;
+            let mut bins = HashMap::with_capacity(initial_capacity(bin_count));
+            for _ in 0..bin_count {
+                let bin = read_u32(reader)?;
+                let chunk_count = read_count(reader)?;
+                let mut chunks = Vec::with_capacity(initial_capacity(chunk_count));
+                for _ in 0..chunk_count {
+                    chunks.push(Chunk {
+                        begin: VirtualOffset(read_u64(reader)?),
+                        end: VirtualOffset(read_u64(reader)?),
+                    });
+                }
+                bins.insert(bin, chunks);
+            }
+            let interval_count = read_count(reader)?;
+            let mut intervals = Vec::with_capacity(initial_capacity(interval_count));
+            for _ in 0..interval_count {
+                intervals.push(VirtualOffset(read_u64(reader)?));
+            }
+            references.push(ReferenceIndex {
+                bins,
+                intervals,
+                ..ReferenceIndex::default()
+            });
+        }
+
+        let unplaced_count = match read_u64(reader) {
+            Ok(x) => Some(x),
+            Err(e) if e.kind() == io::ErrorKind::UnexpectedEof => None,
+            Err(e) => return Err(e.into()),
+        };
+
+        Ok(TabixIndex {
+            format,
+            column_sequence,
+            column_begin,
+            column_end,
+            meta,
+            skip,
+            name_to_index: names
+                .iter()
+                .enumerate()
+                .map(|(k, v)| (v.to_vec(), k))
+                .collect(),
+            names,
+            references,
+            unplaced_count,
+        })
+    }
+
+    /// Write BGZF compressed tabix index.
+    pub fn write<W: Write>(&self, writer: W) -> Result<(), VCFError> {
+        let mut writer = BGZFWriter::new(writer);
+        writer.write_all(b"TBI\x01")?;
+        write_i32(&mut writer, self.names.len() as i32)?;
+        write_i32(&mut writer, self.format)?;
+        write_i32(&mut writer, self.column_sequence)?;
+        write_i32(&mut writer, self.column_begin)?;
+        write_i32(&mut writer, self.column_end)?;
+        write_i32(&mut writer, i32::from(self.meta))?;
+        write_i32(&mut writer, self.skip)?;
+        write_i32(
+            &mut writer,
+            self.names.iter().map(|x| x.len() + 1).sum::<usize>() as i32,
+        )?;
+        for one in self.names.iter() {
+            writer.write_all(one)?;
+            writer.write_all(b"\0")?;
+        }
+
+        for reference in self.references.iter() {
+            let mut bins: Vec<_> = reference.bins.iter().collect();
+            bins.sort();
+            write_i32(&mut writer, bins.len() as i32)?;
+            for (bin, chunks) in bins {
+                write_u32(&mut writer, *bin)?;
+                write_i32(&mut writer, chunks.len() as i32)?;
+                for one in chunks {
+                    write_u64(&mut writer, one.begin.0)?;
+                    write_u64(&mut writer, one.end.0)?;
+                }
+            }
+            write_i32(&mut writer, reference.intervals.len() as i32)?;
+            for one in reference.intervals.iter() {
+                write_u64(&mut writer, one.0)?;
+            }
+        }
+
+        if let Some(unplaced_count) = self.unplaced_count {
+            write_u64(&mut writer, unplaced_count)?;
+        }
+        writer.finish()?;
+        Ok(())
+    }
+
+    /// Sequence names in the index.
+    pub fn names(&self) -> &[U8Vec] {
+        &self.names
+    }
+
+    pub fn format(&self) -> i32 {
+        self.format
+    }
+
+    pub fn column_sequence(&self) -> i32 {
+        self.column_sequence
+    }
+
+    pub fn column_begin(&self) -> i32 {
+        self.column_begin
+    }
+
+    pub fn column_end(&self) -> i32 {
+        self.column_end
+    }
+
+    pub fn meta(&self) -> u8 {
+        self.meta
+    }
+
+    pub fn skip(&self) -> i32 {
+        self.skip
+    }
+
+    /// Number of records without coordinates, if recorded.
+    pub fn unplaced_count(&self) -> Option<u64> {
+        self.unplaced_count
+    }
+
+    /// Chunks which may contain records overlapping with 0-based, half-open region.
+    pub(crate) fn chunks(&self, chromosome: &[u8], begin: u64, end: u64) -> Vec<Chunk> {
+        match self.name_to_index.get(chromosome) {
+            Some(x) => self.references[*x].chunks(begin, end, TABIX_MIN_SHIFT, TABIX_DEPTH),
+            None => Vec::new(),
+        }
+    }
+}
@@ -0,0 +1,468 @@
+use super::*;
+use crate::VCFReader;
+use std::fs::File;
+use std::io::BufReader;
+
+fn load_tabix() -> TabixIndex {
+    TabixIndex::from_reader(File::open("./testfiles/1kGP-subset.vcf.gz.tbi").unwrap()).unwrap()
+}
+
+fn scan_region(chromosome: &[u8], start: u64, end: u64) -> Vec<(U8Vec, u64)> {
+    let mut vcf_reader = VCFReader::new(BufReader::new(
+        &include_bytes!("../../testfiles/1kGP-subset.vcf")[..],
+    ))
+    .unwrap();
+    let mut vcf_record = vcf_reader.empty_record();
+    let mut result = Vec::new();
+    while vcf_reader.next_record(&mut vcf_record).unwrap() {
+        if vcf_record.chromosome == chromosome
+            && vcf_record.position <= end
+            && record_end(&vcf_record) >= start
+        {
+            result.push((vcf_record.chromosome.clone(), vcf_record.position));
+        }
+    }
+    result
+}
+
+fn fetch_region(
+    reader: &mut IndexedVCFReader<File>,
+    chromosome: &[u8],
+    start: u64,
+    end: u64,
+) -> Result<Vec<(U8Vec, u64)>, VCFError> {
+    let mut vcf_record = reader.empty_record();
+    let mut result = Vec::new();
+    reader.fetch(chromosome, start, end)?;
+    while reader.next_record(&mut vcf_record)? {
+        result.push((vcf_record.chromosome.clone(), vcf_record.position));
+    }
+    Ok(result)
+}
+
+#[test]
+fn test_reg2bins() {
+    assert_eq!(
+        reg2bins(0, 1, tabix::TABIX_MIN_SHIFT, tabix::TABIX_DEPTH),
+        vec![0, 1, 9, 73, 585, 4681]
+    );
+    assert_eq!(
+        reg2bins(16_383, 16_385, tabix::TABIX_MIN_SHIFT, tabix::TABIX_DEPTH),
+        vec![0, 1, 9, 73, 585, 4681, 4682]
+    );
+}
+
+#[test]
+fn test_merge_chunks() {
+    let chunk = |x: u64, y: u64| Chunk {
+        begin: VirtualOffset(x),
+        end: VirtualOffset(y),
+    };
+    assert_eq!(
+        merge_chunks(vec![
+            chunk(30, 40),
+            chunk(0, 10),
+            chunk(5, 20),
+            chunk(20, 25)
+        ]),
+        vec![chunk(0, 25), chunk(30, 40)]
+    );
+}
+
+#[test]
+fn test_load_tabix() {
+    let index = load_tabix();
+    assert_eq!(index.names(), &[b"13".to_vec()]);
+    assert_eq!(index.format(), 2);
+    assert_eq!(index.column_sequence(), 1);
+    assert_eq!(index.column_begin(), 2);
+    assert_eq!(index.column_end(), 0);
+    assert_eq!(index.meta(), b'#');
+    assert_eq!(index.skip(), 0);
+    assert!(!index.chunks(b"13", 32_872_835, 32_872_836).is_empty());
+    assert!(index.chunks(b"1", 0, 1_000_000).is_empty());
+
+    assert!(
+        TabixIndex::from_reader(File::open("./testfiles/1kGP-subset.vcf.gz").unwrap()).is_err()
+    );
+}
+
+#[test]
+fn test_load_broken_tabix() {
+    // Counts in the header: sequences, format, columns, meta, skip and names length
+    let header = |names_length: i32| {
+        [1, 2, 1, 2, 0, i32::from(b'#'), 0, names_length]
+            .iter()
+            .flat_map(|x| x.to_le_bytes().to_vec())
+            .collect::<Vec<u8>>()
+    };
+    assert!(matches!(
+        TabixIndex::read_contents(&mut &header(i32::MAX)[..]),
+        Err(VCFError::IndexParseError(_))
+    ));
+    assert!(matches!(
+        TabixIndex::read_contents(&mut &header(1_000_000)[..]),
+        Err(VCFError::IoError(_))
+    ));
+
+    let mut data = header(2);
+    data.extend_from_slice(b"1\0");
+    // Bins and chunks
+    data.extend_from_slice(&i32::MAX.to_le_bytes());
+    data.extend_from_slice(&4681u32.to_le_bytes());
+    data.extend_from_slice(&i32::MAX.to_le_bytes());
+    assert!(matches!(
+        TabixIndex::read_contents(&mut &data[..]),
+        Err(VCFError::IoError(_))
+    ));
+}
+
+#[test]
+fn test_fetch() -> Result<(), VCFError> {
+    let mut reader =
+        IndexedVCFReader::new(File::open("./testfiles/1kGP-subset.vcf.gz")?, load_tabix())?;
+    assert_eq!(reader.header().samples().len(), 5);
+
+    for (chromosome, start, end) in [
+        (&b"13"[..], 32_872_836, 32_872_836),
+        (&b"13"[..], 32_872_837, 32_873_175),
+        (&b"13"[..], 32_905_792, 32_905_792),
+        (&b"13"[..], 1, 32_900_000),
+        (&b"13"[..], 32_950_000, 33_000_000),
+        (&b"13"[..], 1, 1_000_000),
+        (&b"1"[..], 1, 1_000_000_000),
+        (&b"13"[..], 1, u64::MAX),
+        (&b"13"[..], u64::MAX, u64::MAX),
+    ]
+    .iter()
+    {
+        assert_eq!(
+            fetch_region(&mut reader, chromosome, *start, *end)?,
+            scan_region(chromosome, *start, *end)
+        );
+    }
+
+    assert_eq!(
+        fetch_region(&mut reader, b"13", 32_905_792, 32_905_792)?,
+        vec![(b"13".to_vec(), 32_905_789)]
+    );
+    assert_eq!(
+        fetch_region(&mut reader, b"13", 1, 1_000_000_000)?.len(),
+        306
+    );
+    Ok(())
+}
+
+#[test]
+fn test_reg2bin() {
+    assert_eq!(reg2bin(0, 1, 14, 5), 4681);
+    assert_eq!(reg2bin(16_383, 16_384, 14, 5), 4681);
+    assert_eq!(reg2bin(16_384, 16_385, 14, 5), 4682);
+    assert_eq!(reg2bin(16_383, 16_385, 14, 5), 585);
+    assert_eq!(reg2bin(0, 1 << 26, 14, 5), 1);
+    assert_eq!(reg2bin(0, (1 << 26) + 1, 14, 5), 0);
+    assert_eq!(builder::meta_bin(5), 37450);
+}
+
+#[test]
+fn test_write_tabix() -> Result<(), VCFError> {
+    let index = load_tabix();
+    let mut data = Vec::new();
+    index.write(&mut data)?;
+    assert_eq!(TabixIndex::from_reader(&data[..])?, index);
+    Ok(())
+}
+
+#[test]
+fn test_indexed_writer() -> Result<(), VCFError> {
+    let mut vcf_reader = VCFReader::new(BufReader::new(
+        &include_bytes!("../../testfiles/1kGP-subset.vcf")[..],
+    ))?;
+    let mut vcf_record = vcf_reader.empty_record();
+    let mut writer = IndexedVCFWriter::new(Vec::new(), vcf_reader.header())?;
+    while vcf_reader.next_record(&mut vcf_record)? {
+        writer.write_record(&vcf_record)?;
+    }
+    let (data, index) = writer.finish()?;
+    let index = match index {
+        VCFIndex::Tabix(x) => x,
+        VCFIndex::CSI(_) => panic!("tabix index is expected"),
+    };
+
+    let expected = load_tabix();
+    assert_eq!(index.names(), expected.names());
+    assert_eq!(index.format(), expected.format());
+    assert_eq!(index.meta(), expected.meta());
+    let mut bins: Vec<_> = index.references[0].bins.keys().copied().collect();
+    let mut expected_bins: Vec<_> = expected.references[0].bins.keys().copied().collect();
+    bins.sort_unstable();
+    expected_bins.sort_unstable();
+    assert_eq!(bins, expected_bins);
+    assert_eq!(
+        index.references[0].intervals.len(),
+        expected.references[0].intervals.len()
+    );
+    assert_eq!(
+        index.references[0].bins[&builder::meta_bin(5)][1],
+        Chunk {
+            begin: VirtualOffset(306),
+            end: VirtualOffset(0)
+        }
+    );
+
+    let mut index_data = Vec::new();
+    index.write(&mut index_data)?;
+    let mut reader = IndexedVCFReader::new(
+        io::Cursor::new(data),
+        TabixIndex::from_reader(&index_data[..])?,
+    )?;
+    let mut vcf_record = reader.empty_record();
+    for (start, end) in [
+        (32_872_836, 32_872_836),
+        (32_905_792, 32_905_792),
+        (1, 32_900_000),
+        (32_950_000, 33_000_000),
+    ]
+    .iter()
+    {
+        let mut result = Vec::new();
+        reader.fetch(b"13", *start, *end)?;
+        while reader.next_record(&mut vcf_record)? {
+            result.push((vcf_record.chromosome.clone(), vcf_record.position));
+        }
+        assert_eq!(result, scan_region(b"13", *start, *end));
+    }
+
+    Ok(())
+}
+
+#[test]
+fn test_indexed_writer_unsorted() -> Result<(), VCFError> {
+    let header = VCFReader::new(BufReader::new(
+        &include_bytes!("../../testfiles/simple1.vcf")[..],
+    ))?
+    .header()
+    .clone();
+    let mut writer = IndexedVCFWriter::new(Vec::new(), &header)?;
+    for (line, ok) in [
+        (&b"1\t100\t.\tA\tC\t.\t.\t.\n"[..], true),
+        (&b"1\t100\t.\tA\tG\t.\t.\t.\n"[..], true),
+        (&b"2\t50\t.\tA\tC\t.\t.\t.\n"[..], true),
+        (&b"2\t40\t.\tA\tC\t.\t.\t.\n"[..], false),
+        (&b"1\t200\t.\tA\tC\t.\t.\t.\n"[..], false),
+    ]
+    .iter()
+    {
+        let record = VCFRecord::from_bytes(line, 1, header.clone())?;
+        assert_eq!(writer.write_record(&record).is_ok(), *ok);
+    }
+
+    // Rejected records are not written
+    let (data, _) = writer.finish()?;
+    let mut vcf_reader = VCFReader::new(crate::BGZFReader::new(&data[..]))?;
+    let mut positions = Vec::new();
+    for record in vcf_reader.records() {
+        let record = record?;
+        positions.push((record.chromosome, record.position));
+    }
+    assert_eq!(
+        positions,
+        vec![
+            (b"1".to_vec(), 100),
+            (b"1".to_vec(), 100),
+            (b"2".to_vec(), 50)
+        ]
+    );
+    Ok(())
+}
+
+#[test]
+fn test_indexed_reader_parse_error() -> Result<(), VCFError> {
+    let header = VCFReader::new(BufReader::new(
+        &include_bytes!("../../testfiles/simple1.vcf")[..],
+    ))?
+    .header()
+    .clone();
+    let mut writer = IndexedVCFWriter::new(Vec::new(), &header)?;
+    let mut record = VCFRecord::from_bytes(b"1\t100\t.\tA\tC\t.\t.\t.\n", 1, header)?;
+    // Empty REF is written but cannot be parsed
+    record.reference.clear();
+    writer.write_record(&record)?;
+    let (data, index) = writer.finish()?;
+
+    let mut reader = IndexedVCFReader::new(std::io::Cursor::new(data), index)?;
+    let mut record = reader.empty_record();
+    reader.fetch(b"1", 1, 1000)?;
+    match reader.next_record(&mut record) {
+        Err(VCFError::RecordParseError(x)) => {
+            assert_eq!(x.line, None);
+            assert_eq!(&x.field[..], b"REF");
+        }
+        x => panic!("{:?}", x),
+    }
+    Ok(())
+}
+
+fn write_csi(min_shift: u32, depth: u32) -> Result<(Vec<u8>, VCFIndex), VCFError> {
+    let mut vcf_reader = VCFReader::new(BufReader::new(
+        &include_bytes!("../../testfiles/1kGP-subset.vcf")[..],
+    ))?;
+    let mut vcf_record = vcf_reader.empty_record();
+    let mut writer = IndexedVCFWriter::new_csi(Vec::new(), vcf_reader.header(), min_shift, depth)?;
+    while vcf_reader.next_record(&mut vcf_record)? {
+        writer.write_record(&vcf_record)?;
+    }
+    writer.finish()
+}
+
+#[test]
+fn test_csi() -> Result<(), VCFError> {
+    for (min_shift, depth) in [(14, 5), (12, 7), (16, 4)].iter() {
+        let (data, index) = write_csi(*min_shift, *depth)?;
+        let csi = match &index {
+            VCFIndex::CSI(x) => x,
+            VCFIndex::Tabix(_) => panic!("CSI index is expected"),
+        };
+        assert_eq!(csi.names(), &[b"13".to_vec()]);
+        assert_eq!(csi.min_shift(), *min_shift);
+        assert_eq!(csi.depth(), *depth);
+        assert!(csi.references[0].intervals.is_empty());
+        assert_eq!(
+            csi.references[0].bins[&builder::meta_bin(*depth)][1],
+            Chunk {
+                begin: VirtualOffset(306),
+                end: VirtualOffset(0)
+            }
+        );
+
+        let mut index_data = Vec::new();
+        index.write(&mut index_data)?;
+        assert_eq!(CSIIndex::from_reader(&index_data[..])?, *csi);
+        assert!(TabixIndex::from_reader(&index_data[..]).is_err());
+        let loaded = VCFIndex::from_reader(&index_data[..])?;
+        assert_eq!(loaded, index);
+
+        let mut reader = IndexedVCFReader::new(io::Cursor::new(data), loaded)?;
+        for (chromosome, start, end) in [
+            (&b"13"[..], 32_872_836, 32_872_836),
+            (&b"13"[..], 32_872_837, 32_873_175),
+            (&b"13"[..], 32_905_792, 32_905_792),
+            (&b"13"[..], 1, 32_900_000),
+            (&b"13"[..], 32_950_000, 33_000_000),
+            (&b"13"[..], 1, 1_000_000),
+            (&b"1"[..], 1, 1_000_000_000),
+            (&b"13"[..], 1, u64::MAX),
+            (&b"13"[..], u64::MAX, u64::MAX),
+        ]
+        .iter()
+        {
+            let mut vcf_record = reader.empty_record();
+            let mut result = Vec::new();
+            reader.fetch(chromosome, *start, *end)?;
+            while reader.next_record(&mut vcf_record)? {
+                result.push((vcf_record.chromosome.clone(), vcf_record.position));
+            }
+            assert_eq!(result, scan_region(chromosome, *start, *end));
+        }
+    }
+
+    assert_eq!(
+        VCFIndex::from_reader(File::open("./testfiles/1kGP-subset.vcf.gz.tbi")?)?,
+        VCFIndex::Tabix(load_tabix())
+    );
+    Ok(())
+}
+
+#[test]
+fn test_csi_long_chromosome() -> Result<(), VCFError> {
+    let header = VCFReader::new(BufReader::new(
+        &include_bytes!("../../testfiles/simple1.vcf")[..],
+    ))?
+    .header()
+    .clone();
+    let records: Vec<_> = [
+        &b"1\t100\t.\tA\tC\t.\t.\t.\n"[..],
+        &b"1\t600000000\t.\tA\tC\t.\t.\t.\n"[..],
+        &b"1\t900000000\t.\tA\tC\t.\t.\t.\n"[..],
+    ]
+    .iter()
+    .map(|x| VCFRecord::from_bytes(x, 1, header.clone()))
+    .collect::<Result<_, _>>()?;
+
+    let mut writer = IndexedVCFWriter::new(Vec::new(), &header)?;
+    writer.write_record(&records[0])?;
+    assert!(writer.write_record(&records[1]).is_err());
+
+    let mut writer = IndexedVCFWriter::new_csi(Vec::new(), &header, 14, 6)?;
+    for one in records.iter() {
+        writer.write_record(one)?;
+    }
+    let (data, index) = writer.finish()?;
+    let mut reader = IndexedVCFReader::new(io::Cursor::new(data), index)?;
+    let mut vcf_record = reader.empty_record();
+    reader.fetch(b"1", 599_999_000, 700_000_000)?;
+    assert!(reader.next_record(&mut vcf_record)?);
+    assert_eq!(vcf_record.position, 600_000_000);
+    assert!(!reader.next_record(&mut vcf_record)?);
+
+    assert!(IndexedVCFWriter::new_csi(Vec::new(), &header, 14, 20).is_err());
+    Ok(())
+}
+
+#[test]
+fn test_csi_depth_boundary() -> Result<(), VCFError> {
+    assert_eq!(first_bin(11), 1_227_133_513);
+    assert_eq!(builder::meta_bin(10), 1_227_133_514);
+
+    let (data, index) = write_csi(4, 10)?;
+    let mut index_data = Vec::new();
+    index.write(&mut index_data)?;
+    let loaded = CSIIndex::from_reader(&index_data[..])?;
+    assert_eq!(loaded.depth(), 10);
+    let mut reader = IndexedVCFReader::new(io::Cursor::new(data), VCFIndex::CSI(loaded))?;
+    let mut vcf_record = reader.empty_record();
+    let mut result = Vec::new();
+    reader.fetch(b"13", 1, 32_900_000)?;
+    while reader.next_record(&mut vcf_record)? {
+        result.push((vcf_record.chromosome.clone(), vcf_record.position));
+    }
+    assert_eq!(result, scan_region(b"13", 1, 32_900_000));
+
+    let header = VCFReader::new(BufReader::new(
+        &include_bytes!("../../testfiles/simple1.vcf")[..],
+    ))?
+    .header()
+    .clone();
+    for (min_shift, depth) in [(4, 11), (40, 10), (64, 0)].iter() {
+        assert!(matches!(
+            IndexedVCFWriter::new_csi(Vec::new(), &header, *min_shift, *depth),
+            Err(VCFError::InvalidArgument(_))
+        ));
+    }
+
+    // depth is stored after the magic and min_shift
+    let mut uncompressed = Vec::new();
+    BGZFReader::new(&index_data[..]).read_to_end(&mut uncompressed)?;
+    let mut broken = uncompressed.clone();
+    broken[8..12].copy_from_slice(&11i32.to_le_bytes());
+    assert!(matches!(
+        CSIIndex::read_contents(&mut &broken[4..]),
+        Err(VCFError::IndexParseError(_))
+    ));
+
+    // Length of auxiliary data follows depth
+    let mut broken = uncompressed[..16].to_vec();
+    broken[12..16].copy_from_slice(&i32::MAX.to_le_bytes());
+    assert!(matches!(
+        CSIIndex::read_contents(&mut &broken[4..]),
+        Err(VCFError::IndexParseError(_))
+    ));
+    let mut broken = uncompressed[..16].to_vec();
+    broken[12..16].copy_from_slice(&0i32.to_le_bytes());
+    broken.extend_from_slice(&i32::MAX.to_le_bytes());
+    assert!(matches!(
+        CSIIndex::read_contents(&mut &broken[4..]),
+        Err(VCFError::IoError(_))
+    ));
+    Ok(())
+}
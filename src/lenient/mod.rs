@@ -0,0 +1,122 @@
+use crate::{ParseErrorDetail, VCFError};
+
+/// How `VCFReader` handles malformed records.
+///
+/// Only record lines are covered. The header is always parsed strictly, and
+/// records using undeclared INFO or FORMAT keys are accepted without warnings.
+/// Use `VCFHeader::lint` and `VCFRecord::validate` to find such problems.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Strictness {
+    /// Return an error. This is the default.
+    Strict,
+    /// Skip malformed records with a warning.
+    Skip,
+    /// Repair malformed records with a warning if possible, and skip others.
+    /// Bad QUAL, ID, FILTER, FORMAT and sample columns are replaced with `.`,
+    /// and bad INFO entries are removed.
+    Repair,
+}
+
+// `#[default]` on enum variants requires Rust 1.62
+#[allow(clippy::derivable_impls)]
+impl Default for Strictness {
+    fn default() -> Self {
+        Strictness::Strict
+    }
+}
+
+/// Malformed record which was skipped or repaired by a non-strict `VCFReader`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct VCFWarning {
+    /// Location and cause of the problem.
+    pub detail: ParseErrorDetail,
+    /// True if the record was repaired, false if it was skipped.
+    pub repaired: bool,
+}
+
+/// Parse `line` with `parse`, and repair or skip it if it is malformed.
+/// `line` is modified when the record is repaired.
+/// Return false if the record should be skipped.
+pub(crate) fn parse_lenient<F>(
+    strictness: Strictness,
+    line: &mut Vec<u8>,
+    warnings: &mut Vec<VCFWarning>,
+    mut parse: F,
+) -> Result<bool, VCFError>
+where
+    F: FnMut(&[u8]) -> Result<(), VCFError>,
+{
+    loop {
+        let detail = match parse(line) {
+            Ok(()) => return Ok(true),
+            Err(VCFError::RecordParseError(x)) if strictness != Strictness::Strict => x,
+            Err(e) => return Err(e),
+        };
+        let repaired = match strictness {
+            Strictness::Repair => repair_line(line, &detail),
+            _ => None,
+        };
+        let skipped = repaired.is_none();
+        warnings.push(VCFWarning {
+            detail,
+            repaired: !skipped,
+        });
+        match repaired {
+            Some(x) => *line = x,
+            None => return Ok(false),
+        }
+    }
+}
+
+/// Replace the column or INFO entry where `detail` points.
+/// Return `None` if the line cannot be repaired.
+pub(crate) fn repair_line(line: &[u8], detail: &ParseErrorDetail) -> Option<Vec<u8>> {
+    let body = line.strip_suffix(b"\n").unwrap_or(line);
+    let body = body.strip_suffix(b"\r").unwrap_or(body);
+    let offset = detail.column.saturating_sub(1).min(body.len());
+    let index = body[..offset].iter().filter(|x| **x == b'\t').count();
+    // CHROM, POS, REF and ALT are required to identify a variant
+    if index <= 1 || index == 3 || index == 4 || (offset == body.len() && index < 4) {
+        return None;
+    }
+    let start = body[..offset]
+        .iter()
+        .rposition(|x| *x == b'\t')
+        .map(|x| x + 1)
+        .unwrap_or(0);
+    let end = body[offset..]
+        .iter()
+        .position(|x| *x == b'\t')
+        .map(|x| x + offset)
+        .unwrap_or(body.len());
+
+    let mut column = Vec::new();
+    if index == 7 {
+        let mut entry_start = start;
+        for entry in body[start..end].split(|x| *x == b';') {
+            let entry_end = entry_start + entry.len();
+            if !entry.is_empty() && (offset < entry_start || entry_end < offset) {
+                if !column.is_empty() {
+                    column.push(b';');
+                }
+                column.extend_from_slice(entry);
+            }
+            entry_start = entry_end + 1;
+        }
+    }
+    if column.is_empty() {
+        column.push(b'.');
+    }
+
+    let mut repaired = body[..start].to_vec();
+    repaired.extend_from_slice(&column);
+    repaired.extend_from_slice(&line[end..]);
+    if repaired == line {
+        None
+    } else {
+        Some(repaired)
+    }
+}
+
+#[cfg(test)]
+mod test;
@@ -0,0 +1,83 @@
+use super::*;
+use crate::test::write_records;
+use crate::VCFReader;
+use std::io::BufReader;
+
+const DATA: &[u8] = b"##fileformat=VCFv4.2
+##INFO=<ID=DP,Number=1,Type=Integer,Description=\"Depth\">
+#CHROM\tPOS\tID\tREF\tALT\tQUAL\tFILTER\tINFO
+1\t1\t.\tA\tC\t30x\tPASS\tDP=1;=5;AC=1
+1\tX\t.\tA\tC\t.\t.\t.
+1\t3\t.\tA\tC\t.\t.\tDP=3
+";
+
+#[test]
+fn test_repair_line() {
+    let detail = |column, field: &[u8]| ParseErrorDetail::new(1, column, field, b"", "");
+    assert_eq!(
+        repair_line(b"1\t1\t.\tA\tC\t30x\tPASS\n", &detail(13, b"QUAL")),
+        Some(b"1\t1\t.\tA\tC\t.\tPASS\n".to_vec())
+    );
+    assert_eq!(
+        repair_line(b"1\t1\t.\tA\tC\t.\t.\tDP=1;=5;AC=1", &detail(20, b"INFO")),
+        Some(b"1\t1\t.\tA\tC\t.\t.\tDP=1;AC=1".to_vec())
+    );
+    assert_eq!(
+        repair_line(b"1\t1\t.\tA\tC\t.\t.\t=5", &detail(16, b"INFO")),
+        Some(b"1\t1\t.\tA\tC\t.\t.\t.".to_vec())
+    );
+    assert_eq!(repair_line(b"1\tX\t.\tA\tC", &detail(3, b"POS")), None);
+    assert_eq!(repair_line(b"1\t1\t.\tA", &detail(8, b"ALT")), None);
+    assert_eq!(repair_line(b"1\t1\t.\tA\tC\t.", &detail(11, b"QUAL")), None);
+}
+
+#[test]
+fn test_strictness() -> Result<(), VCFError> {
+    let mut reader = VCFReader::new(BufReader::new(DATA))?;
+    assert!(reader.records().next().unwrap().is_err());
+
+    let mut reader = VCFReader::new(BufReader::new(DATA))?;
+    reader.set_strictness(Strictness::Skip);
+    let records = reader.records().collect::<Result<Vec<_>, _>>()?;
+    assert_eq!(write_records(&records), b"1\t3\t.\tA\tC\t.\t.\tDP=3\n");
+    let warnings = reader.take_warnings();
+    assert_eq!(
+        warnings
+            .iter()
+            .map(|x| (x.detail.line, x.repaired))
+            .collect::<Vec<_>>(),
+        vec![(Some(4), false), (Some(5), false)]
+    );
+    assert!(reader.take_warnings().is_empty());
+
+    let mut reader = VCFReader::new(BufReader::new(DATA))?;
+    reader.set_strictness(Strictness::Repair);
+    let records = reader.records().collect::<Result<Vec<_>, _>>()?;
+    assert_eq!(
+        write_records(&records),
+        b"1\t1\t.\tA\tC\t.\tPASS\tDP=1;AC=1\n1\t3\t.\tA\tC\t.\t.\tDP=3\n"
+    );
+    let warnings = reader.take_warnings();
+    assert_eq!(
+        warnings
+            .iter()
+            .map(|x| (x.detail.line, &x.detail.field[..], x.repaired))
+            .collect::<Vec<_>>(),
+        vec![
+            (Some(4), &b"QUAL"[..], true),
+            (Some(4), b"INFO", true),
+            (Some(5), b"POS", false)
+        ]
+    );
+
+    let mut reader = VCFReader::new(BufReader::new(DATA))?;
+    reader.set_strictness(Strictness::Repair);
+    let mut positions = Vec::new();
+    while let Some(view) = reader.next_record_view()? {
+        positions.push((view.position(), view.qual()));
+    }
+    assert_eq!(positions, vec![(1, None), (3, None)]);
+    assert_eq!(reader.take_warnings().len(), 2);
+
+    Ok(())
+}
@@ -58,58 +58,205 @@
 
 use std::io::prelude::*;
 
+#[cfg(feature = "async")]
+mod async_io;
+mod bcf;
+mod bgzf;
 mod error;
 mod header;
+mod index;
+mod lenient;
+mod normalize;
+#[cfg(feature = "parallel")]
+mod parallel;
 mod record;
 
-pub use error::VCFError;
+#[cfg(feature = "async")]
+pub use async_io::{AsyncVCFReader, AsyncVCFWriter};
+pub use bcf::{BCFReader, BCFWriter};
+#[cfg(feature = "parallel")]
+pub use bgzf::ParallelBGZFReader;
+pub use bgzf::{BGZFReader, BGZFWriter, VirtualOffset};
+pub use error::{ParseErrorDetail, VCFError};
 pub use header::{
-    Number, VCFHeader, VCFHeaderContent, VCFHeaderFilterAlt, VCFHeaderInfoFormat, VCFHeaderLine,
-    VCFVersion, ValueType,
+    lint_header, HeaderViolation, Number, VCFHeader, VCFHeaderBuilder, VCFHeaderContent,
+    VCFHeaderContig, VCFHeaderFilterAlt, VCFHeaderInfoFormat, VCFHeaderLine, VCFVersion, ValueType,
 };
-pub use record::VCFRecord;
+pub use index::{CSIIndex, IndexedVCFReader, IndexedVCFWriter, TabixIndex, VCFIndex};
+pub use lenient::{Strictness, VCFWarning};
+pub use normalize::{IndexedFastaReader, VCFNormalizer};
+pub use record::{validate, FormatValue, Genotype, InfoValue, VCFRecord, VCFRecordView, Violation};
 pub type U8Vec = Vec<u8>;
 type VResult<I, O> = nom::IResult<I, O, nom::error::VerboseError<I>>;
 
 pub struct VCFReader<R: BufRead> {
     buffer: Vec<u8>,
-    unprocessed_line: Option<Vec<u8>>,
     current_line: u64,
+    /// False after seeking, because the line number is unknown
+    line_known: bool,
     reader: R,
     vcf_header: VCFHeader,
+    lazy_genotype: bool,
+    file_samples: Vec<U8Vec>,
+    sample_columns: Option<Vec<usize>>,
+    strictness: Strictness,
+    warnings: Vec<VCFWarning>,
 }
 
 impl<R: BufRead> VCFReader<R> {
     pub fn new(mut reader: R) -> Result<Self, VCFError> {
-        let (current_line, unprocessed_line, vcf_header) = header::parse_header(&mut reader)?;
+        let (current_line, vcf_header) = header::parse_header(&mut reader)?;
         Ok(VCFReader {
             buffer: Vec::new(),
-            unprocessed_line,
             current_line,
+            line_known: true,
             reader,
+            file_samples: vcf_header.samples().to_vec(),
             vcf_header,
+            lazy_genotype: false,
+            sample_columns: None,
+            strictness: Strictness::Strict,
+            warnings: Vec::new(),
         })
     }
 
+    /// Read only the samples in the order of `samples`.
+    /// Samples in `header` are also replaced, so create records with `empty_record` after this.
+    pub fn set_samples<T: AsRef<[u8]>>(&mut self, samples: &[T]) -> Result<(), VCFError> {
+        let indices = samples
+            .iter()
+            .map(|x| {
+                self.file_samples
+                    .iter()
+                    .position(|y| y == x.as_ref())
+                    .ok_or_else(|| VCFError::UnknownSample(x.as_ref().to_vec()))
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+        self.set_sample_indices(&indices)
+    }
+
+    /// Read only the samples at 0-based indexes in the file.
+    /// Samples in `header` are also replaced.
+    pub fn set_sample_indices(&mut self, indices: &[usize]) -> Result<(), VCFError> {
+        let samples = indices
+            .iter()
+            .map(|x| {
+                self.file_samples
+                    .get(*x)
+                    .cloned()
+                    .ok_or(VCFError::SampleIndexOutOfRange(*x))
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+        self.vcf_header = VCFHeader::new(self.vcf_header.items().to_vec(), samples);
+        self.sample_columns = Some(indices.to_vec());
+        Ok(())
+    }
+
+    /// Keep sample columns unsplit until a sample or FORMAT key is accessed.
+    /// This is faster if most of genotypes are not used.
+    /// `VCFRecord::genotype` field is empty until `VCFRecord::parse_genotype` is called,
+    /// but `VCFRecord::genotypes` returns all samples.
+    pub fn set_lazy_genotype(&mut self, lazy_genotype: bool) {
+        self.lazy_genotype = lazy_genotype;
+    }
+
+    /// Set how malformed records are handled. Default is `Strictness::Strict`.
+    /// Skipped or repaired records are reported by `take_warnings`.
+    pub fn set_strictness(&mut self, strictness: Strictness) {
+        self.strictness = strictness;
+    }
+
+    /// Warnings collected since the last call of this method.
+    /// Call this periodically to avoid keeping many warnings.
+    pub fn take_warnings(&mut self) -> Vec<VCFWarning> {
+        std::mem::take(&mut self.warnings)
+    }
+
     /// Read next record.
     /// Return false if no record was remained.
     pub fn next_record(&mut self, record: &mut record::VCFRecord) -> Result<bool, VCFError> {
-        if let Some(val) = self.unprocessed_line.as_ref() {
-            self.buffer.extend(val);
-            self.unprocessed_line = None;
-        } else {
+        loop {
             self.buffer.clear();
             self.reader.read_until(b'\n', &mut self.buffer)?;
             self.current_line += 1;
+            if self.buffer.is_empty() {
+                return Ok(false);
+            }
+
+            let line_num = self.line_num();
+            let sample_columns = self.sample_columns.as_deref();
+            let lazy_genotype = self.lazy_genotype;
+            if lenient::parse_lenient(
+                self.strictness,
+                &mut self.buffer,
+                &mut self.warnings,
+                |line| parse_line(record, line, line_num, sample_columns, lazy_genotype),
+            )? {
+                return Ok(true);
+            }
         }
-        if self.buffer.is_empty() {
-            return Ok(false);
+    }
+
+    /// Read next record as a view which borrows from the internal buffer.
+    /// Return `None` if no record was remained.
+    pub fn next_record_view(&mut self) -> Result<Option<VCFRecordView<'_>>, VCFError> {
+        loop {
+            self.buffer.clear();
+            self.reader.read_until(b'\n', &mut self.buffer)?;
+            self.current_line += 1;
+            if self.buffer.is_empty() {
+                return Ok(None);
+            }
+            if self.strictness == Strictness::Strict {
+                break;
+            }
+            let line_num = self.line_num();
+            let header = &self.vcf_header;
+            if lenient::parse_lenient(
+                self.strictness,
+                &mut self.buffer,
+                &mut self.warnings,
+                |line| {
+                    let view = VCFRecordView::new(line, line_num.unwrap_or(0), header);
+                    forget_unknown_line(line_num, view.map(|_| ()))
+                },
+            )? {
+                break;
+            }
         }
+        let sample_columns = self.sample_columns.as_deref();
+        let line_num = self.line_num();
+        let view = VCFRecordView::new(&self.buffer, line_num.unwrap_or(0), &self.vcf_header);
+        forget_unknown_line(line_num, view).map(|x| Some(x.with_sample_subset(sample_columns)))
+    }
 
-        record::parse_record::<nom::error::VerboseError<_>>(&self.buffer, record)
-            .map_err(|_e| VCFError::RecordParseError(self.current_line))?;
+    /// Line number of the current line. `None` after seeking.
+    fn line_num(&self) -> Option<u64> {
+        Some(self.current_line).filter(|_| self.line_known)
+    }
 
-        Ok(true)
+    /// Call `f` for each record. One record is reused for all records.
+    /// Stop reading if `f` returns false.
+    pub fn for_each_record<F, E>(&mut self, mut f: F) -> Result<(), E>
+    where
+        F: FnMut(&VCFRecord) -> Result<bool, E>,
+        E: From<VCFError>,
+    {
+        let mut record = self.empty_record();
+        while self.next_record(&mut record)? {
+            if !f(&record)? {
+                break;
+            }
+        }
+        Ok(())
+    }
+
+    /// Iterator over owned records. The iterator ends after the first error.
+    pub fn records(&mut self) -> VCFRecords<'_, R> {
+        VCFRecords {
+            reader: self,
+            finished: false,
+        }
     }
 
     pub fn header(&self) -> &header::VCFHeader {
@@ -121,6 +268,81 @@ impl<R: BufRead> VCFReader<R> {
     }
 }
 
+/// Parse a line with options of `VCFReader`. `line_num` is `None` if it is unknown.
+fn parse_line(
+    record: &mut VCFRecord,
+    line: &[u8],
+    line_num: Option<u64>,
+    sample_columns: Option<&[usize]>,
+    lazy_genotype: bool,
+) -> Result<(), VCFError> {
+    let result = if lazy_genotype || sample_columns.is_some() {
+        record.parse_bytes_with(line, line_num.unwrap_or(0), sample_columns, lazy_genotype)
+    } else {
+        record.parse_bytes(line, line_num.unwrap_or(0))
+    };
+    forget_unknown_line(line_num, result)
+}
+
+/// Remove the line number from a parse error if `line_num` is `None`.
+fn forget_unknown_line<T>(
+    line_num: Option<u64>,
+    result: Result<T, VCFError>,
+) -> Result<T, VCFError> {
+    match line_num {
+        Some(_) => result,
+        None => result.map_err(VCFError::without_line),
+    }
+}
+
+/// Iterator returned by `VCFReader::records`.
+pub struct VCFRecords<'a, R: BufRead> {
+    reader: &'a mut VCFReader<R>,
+    finished: bool,
+}
+
+impl<'a, R: BufRead> Iterator for VCFRecords<'a, R> {
+    type Item = Result<VCFRecord, VCFError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.finished {
+            return None;
+        }
+        let mut record = self.reader.empty_record();
+        match self.reader.next_record(&mut record) {
+            Ok(true) => Some(Ok(record)),
+            Ok(false) => {
+                self.finished = true;
+                None
+            }
+            Err(e) => {
+                self.finished = true;
+                Some(Err(e))
+            }
+        }
+    }
+}
+
+impl<'a, R: BufRead> std::iter::FusedIterator for VCFRecords<'a, R> {}
+
+impl<R: Read> VCFReader<BGZFReader<R>> {
+    /// Virtual offset of the next record.
+    /// Call this before `next_record` to remember where the record starts.
+    pub fn virtual_offset(&self) -> VirtualOffset {
+        self.reader.virtual_offset()
+    }
+}
+
+impl<R: Read + Seek> VCFReader<BGZFReader<R>> {
+    /// Move to the record at the virtual offset returned by `virtual_offset`.
+    /// Line numbers in errors are unknown after this.
+    pub fn seek(&mut self, offset: VirtualOffset) -> Result<(), VCFError> {
+        self.reader.seek(offset)?;
+        self.line_known = false;
+        Ok(())
+    }
+}
+
 pub struct VCFWriter<W: Write> {
     writer: W,
     // header: VCFHeader,
@@ -128,19 +350,7 @@ pub struct VCFWriter<W: Write> {
 
 impl<W: Write> VCFWriter<W> {
     pub fn new(mut writer: W, header: &VCFHeader) -> Result<Self, VCFError> {
-        for one in header.items() {
-            writer.write_all(one.line())?;
-        }
-        write!(writer, "#CHROM\tPOS\tID\tREF\tALT\tQUAL\tFILTER\tINFO")?;
-        if !header.samples().is_empty() {
-            write!(writer, "\tFORMAT")?;
-            for one in header.samples() {
-                writer.write_all(b"\t")?;
-                writer.write_all(one)?;
-            }
-        }
-        writer.write_all(b"\n")?;
-
+        write_header(&mut writer, header)?;
         Ok(VCFWriter { writer })
     }
 
@@ -150,5 +360,32 @@ impl<W: Write> VCFWriter<W> {
     }
 }
 
+impl<W: Write> VCFWriter<BGZFWriter<W>> {
+    /// Virtual offset of the next record.
+    pub fn virtual_offset(&self) -> VirtualOffset {
+        self.writer.virtual_offset()
+    }
+
+    /// Write remaining records and BGZF EOF marker, then return the inner writer.
+    pub fn finish(self) -> Result<W, VCFError> {
+        Ok(self.writer.finish()?)
+    }
+}
+
+fn write_header<W: Write>(writer: &mut W, header: &VCFHeader) -> std::io::Result<()> {
+    for one in header.items() {
+        writer.write_all(one.line())?;
+    }
+    write!(writer, "#CHROM\tPOS\tID\tREF\tALT\tQUAL\tFILTER\tINFO")?;
+    if !header.samples().is_empty() {
+        write!(writer, "\tFORMAT")?;
+        for one in header.samples() {
+            writer.write_all(b"\t")?;
+            writer.write_all(one)?;
+        }
+    }
+    writer.write_all(b"\n")
+}
+
 #[cfg(test)]
 mod test;
@@ -0,0 +1,95 @@
+use crate::{U8Vec, VCFError};
+use std::collections::HashMap;
+use std::io::{BufRead, Read, Seek, SeekFrom};
+use std::str;
+
+/// One line of FASTA index (`.fai`).
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct FastaIndexEntry {
+    length: u64,
+    offset: u64,
+    line_bases: u64,
+    line_width: u64,
+}
+
+/// Reader for uncompressed FASTA file indexed by `samtools faidx`.
+#[derive(Debug)]
+pub struct IndexedFastaReader<R: Read + Seek> {
+    reader: R,
+    entries: HashMap<U8Vec, FastaIndexEntry>,
+    buffer: Vec<u8>,
+}
+
+impl<R: Read + Seek> IndexedFastaReader<R> {
+    /// Create reader from FASTA file and its `.fai` index.
+    pub fn new(reader: R, fai: impl BufRead) -> Result<Self, VCFError> {
+        let mut entries = HashMap::new();
+        for (i, line) in fai.split(b'\n').enumerate() {
+            let line = line?;
+            let line = line.strip_suffix(b"\r").unwrap_or(&line);
+            if line.is_empty() {
+                continue;
+            }
+            let error = || VCFError::FastaIndexParseError(i as u64 + 1);
+            let columns: Vec<&[u8]> = line.split(|x| *x == b'\t').collect();
+            if columns.len() < 5 {
+                return Err(error());
+            }
+            let values = columns[1..5]
+                .iter()
+                .map(|x| str::from_utf8(x).ok().and_then(|y| y.parse().ok()))
+                .collect::<Option<Vec<u64>>>()
+                .ok_or_else(error)?;
+            if values[2] == 0 || values[3] < values[2] {
+                return Err(error());
+            }
+            entries.insert(
+                columns[0].to_vec(),
+                FastaIndexEntry {
+                    length: values[0],
+                    offset: values[1],
+                    line_bases: values[2],
+                    line_width: values[3],
+                },
+            );
+        }
+        Ok(IndexedFastaReader {
+            reader,
+            entries,
+            buffer: Vec::new(),
+        })
+    }
+
+    /// Length of the sequence.
+    pub fn sequence_length(&self, name: &[u8]) -> Option<u64> {
+        self.entries.get(name).map(|x| x.length)
+    }
+
+    /// Read bases in the region. `start` and `end` are 1-based and inclusive,
+    /// same as VCF positions. The region is clipped at the end of the sequence.
+    pub fn fetch(&mut self, name: &[u8], start: u64, end: u64) -> Result<U8Vec, VCFError> {
+        let entry = self
+            .entries
+            .get(name)
+            .ok_or_else(|| VCFError::UnknownSequence(name.to_vec()))?;
+        let begin = start.saturating_sub(1);
+        let end = end.min(entry.length);
+        if begin >= end {
+            return Ok(Vec::new());
+        }
+        let file_offset =
+            |x: u64| entry.offset + x / entry.line_bases * entry.line_width + x % entry.line_bases;
+        let file_begin = file_offset(begin);
+        let file_end = file_offset(end - 1) + 1;
+
+        self.buffer.resize((file_end - file_begin) as usize, 0);
+        self.reader.seek(SeekFrom::Start(file_begin))?;
+        self.reader.read_exact(&mut self.buffer)?;
+        Ok(self
+            .buffer
+            .iter()
+            .filter(|x| **x != b'\n' && **x != b'\r')
+            .copied()
+            .collect())
+    }
+}
@@ -0,0 +1,124 @@
+mod fasta;
+
+use crate::{U8Vec, VCFError, VCFRecord};
+use std::io::{Read, Seek};
+
+pub use fasta::IndexedFastaReader;
+
+/// Trim and left-align indels against a reference genome like `bcftools norm`.
+#[derive(Debug)]
+pub struct VCFNormalizer<R: Read + Seek> {
+    fasta: IndexedFastaReader<R>,
+}
+
+impl<R: Read + Seek> VCFNormalizer<R> {
+    pub fn new(fasta: IndexedFastaReader<R>) -> Self {
+        VCFNormalizer { fasta }
+    }
+
+    pub fn fasta(&mut self) -> &mut IndexedFastaReader<R> {
+        &mut self.fasta
+    }
+
+    /// Trim common bases of REF and ALT, and shift indels to the leftmost position.
+    /// `position`, `reference` and `alternative` are updated and true is returned if
+    /// the record was changed. Records with symbolic alleles are not changed.
+    /// Returns `VCFError::ReferenceMismatch` if REF does not match the reference genome.
+    pub fn normalize(&mut self, record: &mut VCFRecord) -> Result<bool, VCFError> {
+        let mismatch =
+            |x: &VCFRecord| VCFError::ReferenceMismatch(x.chromosome.clone(), x.position);
+        if record.position == 0 || record.reference.is_empty() {
+            return Err(mismatch(record));
+        }
+        let reference = self.fasta.fetch(
+            &record.chromosome,
+            record.position,
+            record.position + record.reference.len() as u64 - 1,
+        )?;
+        if !reference.eq_ignore_ascii_case(&record.reference) {
+            return Err(mismatch(record));
+        }
+        if record.alternative.is_empty()
+            || record
+                .alternative
+                .iter()
+                .any(|x| !is_sequence(x) || x.eq_ignore_ascii_case(&record.reference))
+        {
+            return Ok(false);
+        }
+
+        let mut position = record.position;
+        let mut alleles: Vec<U8Vec> = Some(&record.reference)
+            .into_iter()
+            .chain(record.alternative.iter())
+            .cloned()
+            .collect();
+
+        // Trim the last base and extend to the left until the last bases differ
+        loop {
+            let last = alleles[0][alleles[0].len() - 1].to_ascii_uppercase();
+            if alleles
+                .iter()
+                .any(|x| x[x.len() - 1].to_ascii_uppercase() != last)
+            {
+                break;
+            }
+            if position <= 1 && alleles.iter().any(|x| x.len() == 1) {
+                break;
+            }
+            for one in alleles.iter_mut() {
+                one.pop();
+            }
+            if alleles.iter().any(|x| x.is_empty()) {
+                position -= 1;
+                let base = self
+                    .fasta
+                    .fetch(&record.chromosome, position, position)?
+                    .first()
+                    .copied()
+                    .ok_or_else(|| {
+                        VCFError::ReferenceMismatch(record.chromosome.clone(), position)
+                    })?
+                    .to_ascii_uppercase();
+                for one in alleles.iter_mut() {
+                    one.insert(0, base);
+                }
+            }
+        }
+
+        // Trim the first bases with keeping at least one base
+        while alleles.iter().all(|x| x.len() >= 2) {
+            let first = alleles[0][0].to_ascii_uppercase();
+            if alleles.iter().any(|x| x[0].to_ascii_uppercase() != first) {
+                break;
+            }
+            for one in alleles.iter_mut() {
+                one.remove(0);
+            }
+            position += 1;
+        }
+
+        let alternative = alleles.split_off(1);
+        let reference = alleles.pop().unwrap();
+        if position == record.position
+            && reference == record.reference
+            && alternative == record.alternative
+        {
+            return Ok(false);
+        }
+        record.position = position;
+        record.reference = reference;
+        record.alternative = alternative;
+        Ok(true)
+    }
+}
+
+fn is_sequence(allele: &[u8]) -> bool {
+    !allele.is_empty()
+        && allele
+            .iter()
+            .all(|x| matches!(x.to_ascii_uppercase(), b'A' | b'C' | b'G' | b'T' | b'N'))
+}
+
+#[cfg(test)]
+mod test;
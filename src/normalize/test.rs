@@ -0,0 +1,121 @@
+use super::*;
+use crate::VCFHeader;
+use std::fs::File;
+use std::io::BufReader;
+
+fn load_fasta() -> IndexedFastaReader<File> {
+    IndexedFastaReader::new(
+        File::open("./testfiles/normalize.fa").unwrap(),
+        BufReader::new(File::open("./testfiles/normalize.fa.fai").unwrap()),
+    )
+    .unwrap()
+}
+
+fn create_header() -> VCFHeader {
+    let vcf_data = &b"##fileformat=VCFv4.2
+#CHROM\tPOS\tID\tREF\tALT\tQUAL\tFILTER\tINFO
+"[..];
+    let (_, header) = crate::header::parse_header(&mut &vcf_data[..]).unwrap();
+    header
+}
+
+#[test]
+fn test_fasta() -> Result<(), VCFError> {
+    let mut fasta = load_fasta();
+    assert_eq!(fasta.sequence_length(b"chr1"), Some(30));
+    assert_eq!(fasta.sequence_length(b"chr3"), None);
+    assert_eq!(fasta.fetch(b"chr1", 1, 4)?, b"ACGT");
+    assert_eq!(fasta.fetch(b"chr1", 8, 13)?, b"TACCAG");
+    assert_eq!(fasta.fetch(b"chr1", 29, 40)?, b"TA");
+    assert_eq!(fasta.fetch(b"chr1", 31, 40)?, b"");
+    assert_eq!(fasta.fetch(b"chr2", 5, 8)?, b"AATT");
+    assert_eq!(fasta.fetch(b"chr2", 1, 9)?, b"GGGAAATTT");
+    assert!(matches!(
+        fasta.fetch(b"chr3", 1, 1),
+        Err(VCFError::UnknownSequence(_))
+    ));
+
+    assert!(matches!(
+        IndexedFastaReader::new(
+            File::open("./testfiles/normalize.fa")?,
+            &b"chr1\t30\t20\t10\t11\nchr2\t9\t74\t6\n"[..]
+        ),
+        Err(VCFError::FastaIndexParseError(2))
+    ));
+    Ok(())
+}
+
+#[test]
+fn test_normalize() -> Result<(), VCFError> {
+    let mut normalizer = VCFNormalizer::new(load_fasta());
+    let header = create_header();
+
+    for (line, expected) in [
+        // deletion in a repeat
+        (
+            &b"chr1\t16\t.\tGCAG\tG\t.\t.\t."[..],
+            Some(&b"chr1\t10\t.\tCCAG\tC\t.\t.\t.\n"[..]),
+        ),
+        // insertion in a homopolymer
+        (
+            b"chr1\t24\t.\tT\tTT\t.\t.\t.",
+            Some(b"chr1\t19\t.\tG\tGT\t.\t.\t.\n"),
+        ),
+        (
+            b"chr1\t24\t.\tT\tTT,TTT\t.\t.\t.",
+            Some(b"chr1\t19\t.\tG\tGT,GTT\t.\t.\t.\n"),
+        ),
+        // trimming
+        (
+            b"chr1\t5\t.\tACGT\tACCT\t.\t.\t.",
+            Some(b"chr1\t7\t.\tG\tC\t.\t.\t.\n"),
+        ),
+        (
+            b"chr1\t16\t.\tgcag\tG\t.\t.\t.",
+            Some(b"chr1\t10\t.\tCCAG\tC\t.\t.\t.\n"),
+        ),
+        (
+            b"chr1\t17\t.\tCAGT\tCAGCAGT\t.\t.\t.",
+            Some(b"chr1\t10\t.\tC\tCCAG\t.\t.\t.\n"),
+        ),
+        // not changed
+        (b"chr1\t7\t.\tG\tC\t.\t.\t.", None),
+        (b"chr1\t10\t.\tCCAG\tC\t.\t.\t.", None),
+        (b"chr1\t1\t.\tA\tAA\t.\t.\t.", None),
+        (b"chr1\t7\t.\tG\t<DEL>\t.\t.\t.", None),
+        (b"chr1\t7\t.\tG\t*,GA\t.\t.\t.", None),
+        (b"chr1\t7\t.\tG\t.\t.\t.\t.", None),
+        (b"chr2\t6\t.\tAT\tA\t.\t.\t.", None),
+    ]
+    .iter()
+    {
+        let mut record = VCFRecord::from_bytes(line, 1, header.clone())?;
+        let changed = normalizer.normalize(&mut record)?;
+        assert_eq!(changed, expected.is_some());
+        let mut written = Vec::new();
+        record.write_record(&mut written)?;
+        let mut original = line.to_vec();
+        original.push(b'\n');
+        assert_eq!(written, expected.map(|x| x.to_vec()).unwrap_or(original));
+    }
+
+    for line in [
+        &b"chr1\t1\t.\tT\tA\t.\t.\t."[..],
+        b"chr1\t29\t.\tTAC\tT\t.\t.\t.",
+    ]
+    .iter()
+    {
+        let mut record = VCFRecord::from_bytes(line, 1, header.clone())?;
+        assert!(matches!(
+            normalizer.normalize(&mut record),
+            Err(VCFError::ReferenceMismatch(_, _))
+        ));
+    }
+
+    let mut record = VCFRecord::from_bytes(b"chr3\t1\t.\tA\tC\t.\t.\t.", 1, header)?;
+    assert!(matches!(
+        normalizer.normalize(&mut record),
+        Err(VCFError::UnknownSequence(_))
+    ));
+    Ok(())
+}
@@ -0,0 +1,90 @@
+use crate::{lenient, parse_line, VCFError, VCFReader, VCFRecord};
+use rayon::prelude::*;
+use std::io::BufRead;
+
+impl<R: BufRead> VCFReader<R> {
+    /// Read up to `batch_size` records and parse them in parallel with rayon.
+    /// Records are stored in `records` in the order of the file and reused between batches.
+    /// Return the number of records read. 0 means that no record was remained.
+    /// Use `rayon::ThreadPool::install` to run with a specific thread pool.
+    pub fn next_batch(
+        &mut self,
+        records: &mut Vec<VCFRecord>,
+        batch_size: usize,
+    ) -> Result<usize, VCFError> {
+        loop {
+            // Lines are stored in one buffer to reuse memory
+            self.buffer.clear();
+            let mut line_ends = Vec::with_capacity(batch_size);
+            while line_ends.len() < batch_size {
+                if self.reader.read_until(b'\n', &mut self.buffer)? == 0 {
+                    break;
+                }
+                line_ends.push(self.buffer.len());
+            }
+            let first_line = self.line_num().map(|x| x + 1);
+            self.current_line += line_ends.len() as u64;
+
+            let mut begin = 0;
+            let buffer = &self.buffer;
+            let lines: Vec<&[u8]> = line_ends
+                .iter()
+                .map(|end| {
+                    let line = &buffer[begin..*end];
+                    begin = *end;
+                    line
+                })
+                .collect();
+
+            records.truncate(lines.len());
+            while records.len() < lines.len() {
+                records.push(VCFRecord::new(self.vcf_header.clone()));
+            }
+
+            let sample_columns = self.sample_columns.as_deref();
+            let lazy_genotype = self.lazy_genotype;
+            let results = records
+                .par_iter_mut()
+                .zip(lines.par_iter())
+                .enumerate()
+                .map(|(i, (record, line))| {
+                    let line_num = first_line.map(|x| x + i as u64);
+                    parse_line(record, line, line_num, sample_columns, lazy_genotype)
+                })
+                .collect::<Vec<_>>();
+
+            // Malformed records are handled one by one in the order of the file
+            let mut keep = vec![true; lines.len()];
+            for (i, result) in results.into_iter().enumerate() {
+                let error = match result {
+                    Ok(()) => continue,
+                    Err(e) => e,
+                };
+                let line_num = first_line.map(|x| x + i as u64);
+                let record = &mut records[i];
+                let mut first_error = Some(error);
+                keep[i] = lenient::parse_lenient(
+                    self.strictness,
+                    &mut lines[i].to_vec(),
+                    &mut self.warnings,
+                    |line| match first_error.take() {
+                        Some(e) => Err(e),
+                        None => parse_line(record, line, line_num, sample_columns, lazy_genotype),
+                    },
+                )?;
+            }
+
+            let mut index = 0;
+            records.retain(|_| {
+                index += 1;
+                keep[index - 1]
+            });
+            if !records.is_empty() || lines.is_empty() {
+                return Ok(records.len());
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod test;
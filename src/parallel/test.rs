@@ -0,0 +1,84 @@
+use super::*;
+use crate::test::write_records;
+use std::io::BufReader;
+
+#[test]
+fn test_next_batch() -> Result<(), VCFError> {
+    let data = &include_bytes!("../../testfiles/1kGP-subset.vcf")[..];
+    let mut vcf_reader = VCFReader::new(BufReader::new(data))?;
+    let expected = vcf_reader.records().collect::<Result<Vec<_>, _>>()?;
+
+    for batch_size in [1, 7, 100, 1000].iter() {
+        let mut vcf_reader = VCFReader::new(BufReader::new(data))?;
+        let mut records = Vec::new();
+        let mut all_records = Vec::new();
+        loop {
+            let count = vcf_reader.next_batch(&mut records, *batch_size)?;
+            assert_eq!(count, records.len());
+            if count == 0 {
+                break;
+            }
+            assert!(count <= *batch_size);
+            all_records.extend(records.iter().cloned());
+        }
+        assert_eq!(write_records(&all_records), write_records(&expected));
+    }
+
+    let mut vcf_reader = VCFReader::new(BufReader::new(data))?;
+    let samples = vec![vcf_reader.header().samples()[2].clone()];
+    vcf_reader.set_samples(&samples)?;
+    vcf_reader.set_lazy_genotype(true);
+    let mut records = Vec::new();
+    vcf_reader.next_batch(&mut records, 10)?;
+    assert_eq!(records.len(), 10);
+    for (record, expected) in records.iter().zip(expected.iter()) {
+        assert!(record.is_genotype_lazy());
+        assert_eq!(
+            record.genotype(&samples[0], b"GT"),
+            expected.genotype(&samples[0], b"GT")
+        );
+    }
+    Ok(())
+}
+
+#[test]
+fn test_next_batch_error() -> Result<(), VCFError> {
+    let data = &b"##fileformat=VCFv4.2
+#CHROM\tPOS\tID\tREF\tALT\tQUAL\tFILTER\tINFO
+1\t1\t.\tA\tC\t.\t.\t.
+1\t2\t.\tA\tC\t.\t.\t.
+1\tX
+1\t4\t.\tA\tC\t.\t.\t.
+1\tY
+"[..];
+    let mut vcf_reader = VCFReader::new(BufReader::new(data))?;
+    let mut records = Vec::new();
+    assert_eq!(vcf_reader.next_batch(&mut records, 2)?, 2);
+    assert!(matches!(
+        vcf_reader.next_batch(&mut records, 10),
+        Err(VCFError::RecordParseError(x)) if x.line == Some(5)
+    ));
+    Ok(())
+}
+
+#[test]
+fn test_next_batch_lenient() -> Result<(), VCFError> {
+    let data = &b"##fileformat=VCFv4.2
+#CHROM\tPOS\tID\tREF\tALT\tQUAL\tFILTER\tINFO
+1\tX\t.\tA\tC\t.\t.\t.
+1\t2\t.\tA\tC\t3x\t.\t.
+1\tY\t.\tA\tC\t.\t.\t.
+1\t4\t.\tA\tC\t.\t.\t.
+"[..];
+    let mut vcf_reader = VCFReader::new(BufReader::new(data))?;
+    vcf_reader.set_strictness(crate::Strictness::Repair);
+    let mut records = Vec::new();
+    assert_eq!(vcf_reader.next_batch(&mut records, 1)?, 1);
+    assert_eq!(records[0].position, 2);
+    assert_eq!(records[0].qual, None);
+    assert_eq!(vcf_reader.next_batch(&mut records, 2)?, 1);
+    assert_eq!(records[0].position, 4);
+    assert_eq!(vcf_reader.next_batch(&mut records, 2)?, 0);
+    assert_eq!(vcf_reader.take_warnings().len(), 3);
+    Ok(())
+}
@@ -0,0 +1,134 @@
+use crate::{U8Vec, VCFError};
+use std::fmt;
+use std::str::{self, FromStr};
+
+/// Parsed `GT` field, such as `0/1`, `1|0`, `./.` or `1`.
+#[derive(Debug, Clone, Hash, PartialEq, Eq)]
+pub struct Genotype {
+    alleles: Vec<Option<usize>>,
+    phased: Vec<bool>,
+}
+
+impl Genotype {
+    /// Create genotype. `None` in `alleles` is a missing allele.
+    /// All alleles are separated by `|` if `phased` is true, otherwise `/`.
+    pub fn new(alleles: Vec<Option<usize>>, phased: bool) -> Self {
+        Genotype {
+            phased: vec![phased; alleles.len().saturating_sub(1)],
+            alleles,
+        }
+    }
+
+    /// Create genotype with phasing of each separator.
+    /// Return `InvalidArgument` if the length of `phased` is not `alleles.len() - 1`.
+    pub fn from_parts(alleles: Vec<Option<usize>>, phased: Vec<bool>) -> Result<Self, VCFError> {
+        if phased.len() != alleles.len().saturating_sub(1) {
+            return Err(VCFError::InvalidArgument(
+                "Number of phasing does not match alleles",
+            ));
+        }
+        Ok(Genotype { alleles, phased })
+    }
+
+    pub fn from_bytes(value: &[u8]) -> Result<Self, VCFError> {
+        let error = || VCFError::TypedValueError(b"GT".to_vec(), "Invalid genotype");
+        let mut alleles = Vec::new();
+        let mut phased = Vec::new();
+        let mut rest = value;
+        loop {
+            let end = rest
+                .iter()
+                .position(|x| *x == b'/' || *x == b'|')
+                .unwrap_or(rest.len());
+            alleles.push(match &rest[..end] {
+                b"." => None,
+                x => Some(
+                    str::from_utf8(x)
+                        .ok()
+                        .filter(|y| y.bytes().all(|z| z.is_ascii_digit()))
+                        .and_then(|y| y.parse().ok())
+                        .ok_or_else(error)?,
+                ),
+            });
+            if end == rest.len() {
+                break;
+            }
+            phased.push(rest[end] == b'|');
+            rest = &rest[end + 1..];
+        }
+        Ok(Genotype { alleles, phased })
+    }
+
+    pub fn to_bytes(&self) -> U8Vec {
+        self.to_string().into_bytes()
+    }
+
+    /// Allele indexes. 0 is the reference allele and `None` is a missing allele.
+    pub fn alleles(&self) -> &[Option<usize>] {
+        &self.alleles
+    }
+
+    /// Phasing of each separator. The length is `ploidy() - 1`.
+    pub fn phasing(&self) -> &[bool] {
+        &self.phased
+    }
+
+    /// True if all separators are `|`. Haploid calls are not phased.
+    pub fn is_phased(&self) -> bool {
+        !self.phased.is_empty() && self.phased.iter().all(|x| *x)
+    }
+
+    pub fn ploidy(&self) -> usize {
+        self.alleles.len()
+    }
+
+    /// True if all alleles are missing.
+    pub fn is_missing(&self) -> bool {
+        self.alleles.iter().all(|x| x.is_none())
+    }
+
+    /// True if all alleles are the reference allele.
+    pub fn is_hom_ref(&self) -> bool {
+        !self.alleles.is_empty() && self.alleles.iter().all(|x| *x == Some(0))
+    }
+
+    /// True if all alleles are the same alternative allele.
+    pub fn is_hom_alt(&self) -> bool {
+        match self.alleles.first() {
+            Some(Some(first)) if *first != 0 => self.alleles.iter().all(|x| *x == Some(*first)),
+            _ => false,
+        }
+    }
+
+    /// True if at least two different alleles are called. Missing alleles are ignored.
+    pub fn is_het(&self) -> bool {
+        let mut called = self.alleles.iter().flatten();
+        match called.next() {
+            Some(first) => called.any(|x| x != first),
+            None => false,
+        }
+    }
+}
+
+impl fmt::Display for Genotype {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for (i, one) in self.alleles.iter().enumerate() {
+            if i != 0 {
+                f.write_str(if self.phased[i - 1] { "|" } else { "/" })?;
+            }
+            match one {
+                Some(x) => write!(f, "{}", x)?,
+                None => f.write_str(".")?,
+            }
+        }
+        Ok(())
+    }
+}
+
+impl FromStr for Genotype {
+    type Err = VCFError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Genotype::from_bytes(s.as_bytes())
+    }
+}
@@ -0,0 +1,95 @@
+use crate::U8Vec;
+use once_cell::unsync::OnceCell;
+use std::ops::Range;
+
+/// Sample columns which are split on first access of each sample.
+#[derive(Debug, Clone, Default)]
+pub(crate) struct LazyGenotype {
+    data: U8Vec,
+    ranges: OnceCell<Vec<Range<usize>>>,
+    samples: OnceCell<Vec<OnceCell<Vec<Vec<U8Vec>>>>>,
+    all: OnceCell<Vec<Vec<Vec<U8Vec>>>>,
+}
+
+impl PartialEq for LazyGenotype {
+    fn eq(&self, other: &Self) -> bool {
+        self.data == other.data
+    }
+}
+
+impl LazyGenotype {
+    /// Create from tab separated sample columns.
+    pub fn new(data: &[u8]) -> Self {
+        let data = data.strip_suffix(b"\n").unwrap_or(data);
+        let data = data.strip_suffix(b"\r").unwrap_or(data);
+        LazyGenotype {
+            data: data.to_vec(),
+            ranges: OnceCell::new(),
+            samples: OnceCell::new(),
+            all: OnceCell::new(),
+        }
+    }
+
+    pub fn raw(&self) -> &[u8] {
+        &self.data
+    }
+
+    fn ranges(&self) -> &[Range<usize>] {
+        self.ranges.get_or_init(|| {
+            let mut ranges = Vec::new();
+            let mut begin = 0;
+            for (i, one) in self.data.iter().enumerate() {
+                if *one == b'\t' {
+                    ranges.push(begin..i);
+                    begin = i + 1;
+                }
+            }
+            ranges.push(begin..self.data.len());
+            ranges
+        })
+    }
+
+    pub fn sample_count(&self) -> usize {
+        self.ranges().len()
+    }
+
+    pub fn sample(&self, index: usize) -> Option<&Vec<Vec<U8Vec>>> {
+        let range = self.ranges().get(index)?.clone();
+        let samples = self
+            .samples
+            .get_or_init(|| vec![OnceCell::new(); self.sample_count()]);
+        Some(samples[index].get_or_init(|| split_sample(&self.data[range])))
+    }
+
+    /// All samples, split on first access.
+    pub fn genotype(&self) -> &[Vec<Vec<U8Vec>>] {
+        self.all.get_or_init(|| {
+            (0..self.sample_count())
+                .map(|x| self.sample(x).cloned().unwrap_or_default())
+                .collect()
+        })
+    }
+
+    /// Split all samples.
+    pub fn into_genotype(mut self) -> Vec<Vec<Vec<U8Vec>>> {
+        if let Some(x) = self.all.take() {
+            return x;
+        }
+        let ranges = self.ranges().to_vec();
+        let data = self.data;
+        let mut samples = self.samples.into_inner().unwrap_or_default();
+        samples.resize(ranges.len(), OnceCell::new());
+        samples
+            .into_iter()
+            .zip(ranges)
+            .map(|(x, y)| x.into_inner().unwrap_or_else(|| split_sample(&data[y])))
+            .collect()
+    }
+}
+
+/// Split one sample column into FORMAT fields and values.
+fn split_sample(data: &[u8]) -> Vec<Vec<U8Vec>> {
+    data.split(|x| *x == b':')
+        .map(|x| x.split(|y| *y == b',').map(|y| y.to_vec()).collect())
+        .collect()
+}
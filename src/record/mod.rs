@@ -1,10 +1,21 @@
+mod genotype;
+mod lazy;
 mod parser;
+mod split;
+mod validate;
+mod value;
+mod view;
 
-use crate::{U8Vec, VCFError, VCFHeader};
+use crate::{U8Vec, VCFError, VCFHeader, ValueType};
+pub use genotype::Genotype;
+use lazy::LazyGenotype;
 pub use parser::parse_record;
+use std::borrow::Cow;
 use std::collections::HashMap;
 use std::io::{self, Write};
-use std::usize;
+pub use validate::{validate, Violation};
+pub use value::{FormatValue, InfoValue};
+pub use view::VCFRecordView;
 
 pub const NOT_FOUND: usize = usize::MAX;
 
@@ -22,7 +33,11 @@ pub struct VCFRecord {
     info_index: HashMap<U8Vec, usize>,
     pub format: Vec<U8Vec>,
     format_index: HashMap<U8Vec, usize>,
+    /// Empty while sample columns are kept unsplit by `VCFReader::set_lazy_genotype`.
+    /// Use `genotypes` or call `parse_genotype` first in that case.
     pub genotype: Vec<Vec<Vec<U8Vec>>>,
+    /// Sample columns which are not split into `genotype` yet
+    pub(crate) lazy_genotype: Option<LazyGenotype>,
 }
 
 impl VCFRecord {
@@ -41,6 +56,7 @@ impl VCFRecord {
             format: vec![],
             format_index: HashMap::new(),
             genotype: vec![],
+            lazy_genotype: None,
         }
     }
 
@@ -51,11 +67,108 @@ impl VCFRecord {
     }
 
     pub fn parse_bytes(&mut self, line: &[u8], line_num: u64) -> Result<(), VCFError> {
-        parse_record::<nom::error::VerboseError<_>>(line, self)
-            .map_err(|_| -> VCFError { VCFError::RecordParseError(line_num).into() })?;
+        parse_record(line, self)
+            .map_err(|e| parser::record_parse_error(line, line_num, e, &self.header))?;
         Ok(())
     }
 
+    /// Parse record with options of `VCFReader`.
+    /// Only sample columns at `sample_columns`, which are 0-based indexes in the line,
+    /// are kept if it is set. Sample columns are kept unsplit until they are accessed
+    /// if `lazy_genotype` is true.
+    pub(crate) fn parse_bytes_with(
+        &mut self,
+        line: &[u8],
+        line_num: u64,
+        sample_columns: Option<&[usize]>,
+        lazy_genotype: bool,
+    ) -> Result<(), VCFError> {
+        let line = line.strip_suffix(b"\n").unwrap_or(line);
+        let line = line.strip_suffix(b"\r").unwrap_or(line);
+        let format_end = match line.iter().enumerate().filter(|(_, x)| **x == b'\t').nth(8) {
+            Some((x, _)) => x,
+            None => return self.parse_bytes(line, line_num),
+        };
+
+        let samples = match sample_columns {
+            Some([]) => {
+                self.parse_bytes(&line[..format_end], line_num)?;
+                self.format.clear();
+                self.recreate_info_and_genotype_index();
+                return Ok(());
+            }
+            Some(columns) => {
+                let all: Vec<&[u8]> = line[format_end + 1..].split(|x| *x == b'\t').collect();
+                let mut samples = Vec::new();
+                for (i, one) in columns.iter().enumerate() {
+                    if i != 0 {
+                        samples.push(b'\t');
+                    }
+                    samples.extend_from_slice(all.get(*one).copied().unwrap_or(b"."));
+                }
+                Cow::Owned(samples)
+            }
+            None => Cow::Borrowed(&line[format_end + 1..]),
+        };
+
+        if lazy_genotype {
+            self.parse_bytes(&line[..format_end], line_num)?;
+            if !self.format.is_empty() {
+                self.lazy_genotype = Some(LazyGenotype::new(&samples));
+            }
+        } else if let Cow::Owned(samples) = samples {
+            let mut subset_line = line[..=format_end].to_vec();
+            subset_line.extend_from_slice(&samples);
+            self.parse_bytes(&subset_line, line_num)?;
+        } else {
+            self.parse_bytes(line, line_num)?;
+        }
+        Ok(())
+    }
+
+    /// Split sample columns if they were kept unsplit by `VCFReader::set_lazy_genotype`.
+    /// Other methods call this when needed.
+    pub fn parse_genotype(&mut self) {
+        if let Some(x) = self.lazy_genotype.take() {
+            self.genotype = x.into_genotype();
+        }
+    }
+
+    /// Values of all samples, indexed by sample and then by FORMAT key.
+    /// Unsplit sample columns are split on first access.
+    pub fn genotypes(&self) -> &[Vec<Vec<U8Vec>>] {
+        match self.lazy_genotype.as_ref() {
+            Some(x) => x.genotype(),
+            None => &self.genotype,
+        }
+    }
+
+    /// Mutable version of `genotypes`. Unsplit sample columns are moved to `genotype`.
+    pub fn genotypes_mut(&mut self) -> &mut Vec<Vec<Vec<U8Vec>>> {
+        self.parse_genotype();
+        &mut self.genotype
+    }
+
+    /// True if sample columns are not split yet.
+    pub fn is_genotype_lazy(&self) -> bool {
+        self.lazy_genotype.is_some()
+    }
+
+    /// FORMAT fields and values of the sample.
+    pub(crate) fn sample_genotype(&self, sample_index: usize) -> Option<&Vec<Vec<U8Vec>>> {
+        match self.lazy_genotype.as_ref() {
+            Some(x) => x.sample(sample_index),
+            None => self.genotype.get(sample_index),
+        }
+    }
+
+    pub(crate) fn sample_genotype_count(&self) -> usize {
+        match self.lazy_genotype.as_ref() {
+            Some(x) => x.sample_count(),
+            None => self.genotype.len(),
+        }
+    }
+
     pub fn header(&self) -> &VCFHeader {
         &self.header
     }
@@ -63,16 +176,36 @@ impl VCFRecord {
     pub fn info(&self, key: &[u8]) -> Option<&Vec<U8Vec>> {
         self.info_index
             .get(key)
-            .map(|x| self.info.get(*x).map(|y| &y.1))
-            .flatten()
+            .and_then(|x| self.info.get(*x).map(|y| &y.1))
+    }
+
+    /// Parse INFO value according to the header declaration.
+    /// Return `None` if the record does not have the key.
+    pub fn info_value(&self, key: &[u8]) -> Result<Option<InfoValue>, VCFError> {
+        let values = match self.info(key) {
+            Some(x) => x,
+            None => return Ok(None),
+        };
+        let definition = self
+            .header
+            .info(key)
+            .ok_or_else(|| VCFError::TypedValueError(key.to_vec(), "Not defined in header"))?;
+        value::parse_values(
+            key,
+            values,
+            definition.value_type,
+            definition.number,
+            self.alternative.len() + 1,
+            2,
+        )
+        .map(Some)
     }
 
     pub fn info_mut(&mut self, key: &[u8]) -> Option<&mut Vec<U8Vec>> {
         self.info_index
             .get(key)
             .cloned()
-            .map(move |x| self.info.get_mut(x).map(|y| &mut y.1))
-            .flatten()
+            .and_then(move |x| self.info.get_mut(x).map(|y| &mut y.1))
     }
 
     pub fn insert_info(&mut self, key: &[u8], mut values: Vec<U8Vec>) -> Option<Vec<U8Vec>> {
@@ -91,20 +224,146 @@ impl VCFRecord {
     pub fn genotype(&self, sample_name: &[u8], key: &[u8]) -> Option<&Vec<U8Vec>> {
         self.header
             .sample_index(sample_name)
-            .map(|x| {
+            .and_then(|x| {
                 self.format_index
                     .get(key)
-                    .map(|y| self.genotype.get(x).map(|z| z.get(*y)))
+                    .map(|y| self.sample_genotype(x).map(|z| z.get(*y)))
             })
             .flatten()
             .flatten()
-            .flatten()
+    }
+
+    /// Parse FORMAT value of the sample according to the header declaration.
+    /// Return `None` if the sample or the key is not found.
+    pub fn genotype_value(
+        &self,
+        sample_name: &[u8],
+        key: &[u8],
+    ) -> Result<Option<FormatValue>, VCFError> {
+        match (
+            self.header.sample_index(sample_name),
+            self.format_index.get(key),
+        ) {
+            (Some(sample_index), Some(_)) => self.parse_genotype_value(sample_index, key).map(Some),
+            _ => Ok(None),
+        }
+    }
+
+    /// Parse FORMAT values of all samples according to the header declaration.
+    /// Return `None` if the record does not have the key.
+    pub fn genotype_column(&self, key: &[u8]) -> Result<Option<Vec<FormatValue>>, VCFError> {
+        if !self.format_index.contains_key(key) {
+            return Ok(None);
+        }
+        (0..self.sample_genotype_count())
+            .map(|x| self.parse_genotype_value(x, key))
+            .collect::<Result<_, _>>()
+            .map(Some)
+    }
+
+    /// First values of integer FORMAT field, such as `DP` or `GQ`, of all samples.
+    /// All values are `None` if the record does not have the key.
+    pub fn genotype_integer_column(&self, key: &[u8]) -> Result<Vec<Option<i32>>, VCFError> {
+        self.genotype_first_values(key, ValueType::Integer, |x| {
+            x.integers().and_then(|y| y.first().copied().flatten())
+        })
+    }
+
+    /// First values of float FORMAT field of all samples.
+    /// All values are `None` if the record does not have the key.
+    pub fn genotype_float_column(&self, key: &[u8]) -> Result<Vec<Option<f64>>, VCFError> {
+        self.genotype_first_values(key, ValueType::Float, |x| {
+            x.floats().and_then(|y| y.first().copied().flatten())
+        })
+    }
+
+    fn genotype_first_values<T>(
+        &self,
+        key: &[u8],
+        value_type: ValueType,
+        first: impl Fn(&FormatValue) -> Option<T>,
+    ) -> Result<Vec<Option<T>>, VCFError> {
+        let definition = self
+            .header
+            .format(key)
+            .ok_or_else(|| VCFError::TypedValueError(key.to_vec(), "Not defined in header"))?;
+        if *definition.value_type != value_type {
+            return Err(VCFError::TypedValueError(key.to_vec(), "Unexpected type"));
+        }
+        match self.genotype_column(key)? {
+            Some(x) => Ok(x.iter().map(first).collect()),
+            None => Ok((0..self.header.samples().len()).map(|_| None).collect()),
+        }
+    }
+
+    fn parse_genotype_value(
+        &self,
+        sample_index: usize,
+        key: &[u8],
+    ) -> Result<FormatValue, VCFError> {
+        let definition = self
+            .header
+            .format(key)
+            .ok_or_else(|| VCFError::TypedValueError(key.to_vec(), "Not defined in header"))?;
+        let empty = Vec::new();
+        let sample = self.sample_genotype(sample_index).unwrap_or(&empty);
+        let get = |x: &[u8]| {
+            self.format_index
+                .get(x)
+                .and_then(|y| sample.get(*y))
+                .filter(|y| !y.is_empty())
+        };
+        // Omitted trailing fields are missing values
+        let missing = vec![b".".to_vec()];
+        let values = get(key).unwrap_or(&missing);
+        let ploidy = get(b"GT")
+            .map(|x| x[0].split(|y| *y == b'/' || *y == b'|').count())
+            .unwrap_or(2);
+        value::parse_values(
+            key,
+            values,
+            definition.value_type,
+            definition.number,
+            self.alternative.len() + 1,
+            ploidy,
+        )
+    }
+
+    /// Parse `GT` field of the sample.
+    /// Return `None` if the sample or `GT` field is not found.
+    pub fn genotype_call(&self, sample_name: &[u8]) -> Result<Option<Genotype>, VCFError> {
+        match self.genotype(sample_name, b"GT").and_then(|x| x.first()) {
+            Some(x) => Genotype::from_bytes(x).map(Some),
+            None => Ok(None),
+        }
+    }
+
+    /// Set `GT` field of the sample. `GT` is added as the first FORMAT key if it is not found.
+    /// Return false if the sample is not found.
+    pub fn set_genotype_call(&mut self, sample_name: &[u8], genotype: &Genotype) -> bool {
+        if self.header.sample_index(sample_name).is_none() {
+            return false;
+        }
+        self.parse_genotype();
+        if !self.format_index.contains_key(&b"GT"[..]) {
+            self.format.insert(0, b"GT".to_vec());
+            if self.genotype.is_empty() {
+                self.genotype = vec![Vec::new(); self.header.samples().len()];
+            }
+            for one in self.genotype.iter_mut() {
+                one.insert(0, vec![b".".to_vec()]);
+            }
+            self.recreate_info_and_genotype_index();
+        }
+        self.insert_genotype(sample_name, b"GT", vec![genotype.to_bytes()]);
+        true
     }
 
     pub fn genotype_mut(&mut self, sample_name: &[u8], key: &[u8]) -> Option<&mut Vec<U8Vec>> {
+        self.parse_genotype();
         self.header
             .sample_index(sample_name)
-            .map(move |x| {
+            .and_then(move |x| {
                 self.format_index
                     .get(key)
                     .cloned()
@@ -112,7 +371,6 @@ impl VCFRecord {
             })
             .flatten()
             .flatten()
-            .flatten()
     }
 
     pub fn insert_genotype(
@@ -121,6 +379,7 @@ impl VCFRecord {
         key: &[u8],
         mut values: Vec<U8Vec>,
     ) -> Option<Vec<U8Vec>> {
+        self.parse_genotype();
         if let Some(sample_index) = self.header.sample_index(sample_name) {
             if let Some(x) = self.genotype_mut(sample_name, key) {
                 let mut ret = Vec::new();
@@ -243,6 +502,10 @@ impl VCFRecord {
         if !self.format.is_empty() {
             writer.write_all(b"\t")?;
             write_array(&mut writer, &self.format, b":")?;
+            if let Some(x) = self.lazy_genotype.as_ref() {
+                writer.write_all(b"\t")?;
+                writer.write_all(x.raw())?;
+            }
             for one_genotype in self.genotype.iter() {
                 writer.write_all(b"\t")?;
                 for (i, v) in one_genotype.iter().enumerate() {
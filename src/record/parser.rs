@@ -1,8 +1,8 @@
 use super::VCFRecord;
-use crate::U8Vec;
+use crate::{ParseErrorDetail, U8Vec, VCFError, VCFHeader, VResult};
 use nom::{
     self, branch::alt, bytes::complete::is_not, bytes::complete::tag, bytes::complete::take_while1,
-    character::is_digit, combinator::opt, combinator::recognize, sequence::tuple,
+    character::is_digit, combinator::opt, combinator::recognize, error::context, sequence::tuple,
 };
 use once_cell::sync::Lazy;
 use std::str;
@@ -219,7 +219,7 @@ fn parse_record_optional_columns<'a, E>(
     record: &mut VCFRecord,
 ) -> nom::IResult<&'a [u8], (), E>
 where
-    E: nom::error::ParseError<&'a [u8]>,
+    E: nom::error::ParseError<&'a [u8]> + nom::error::ContextError<&'a [u8]>,
 {
     let rest = match tag::<_, _, E>(b"\t")(rest) {
         Ok((rest, _)) => rest,
@@ -232,7 +232,7 @@ where
             return Ok((rest, ()));
         }
     };
-    let (rest, qual) = parse_float(rest)?;
+    let (rest, qual) = context("Quality is not a number", parse_float)(rest)?;
     if qual == b"." {
         record.qual = None;
     } else {
@@ -323,16 +323,14 @@ where
     }
 }
 
-pub fn parse_record<'a, E>(line: &'a [u8], record: &mut VCFRecord) -> nom::IResult<&'a [u8], ()>
-where
-    E: nom::error::ParseError<&'a [u8]>,
-{
-    let (rest, chromosome) = is_not(&b"\t\r\n"[..])(line)?;
+pub fn parse_record<'a>(line: &'a [u8], record: &mut VCFRecord) -> VResult<&'a [u8], ()> {
+    record.lazy_genotype = None;
+    let (rest, chromosome) = context("Invalid chromosome", is_not(&b"\t\r\n"[..]))(line)?;
     record.chromosome.clear();
     record.chromosome.extend_from_slice(chromosome);
     let (rest, _) = tag(b"\t")(rest)?;
 
-    let (rest, position) = take_while1(is_digit)(rest)?;
+    let (rest, position) = context("Position is not an integer", take_while1(is_digit))(rest)?;
     record.position = str::from_utf8(position).unwrap().parse().unwrap();
     let (rest, _) = tag(b"\t")(rest)?;
 
@@ -347,7 +345,7 @@ where
         record.id.clear();
     }
     let (rest, _) = tag(b"\t")(rest)?;
-    let (rest, reference) = is_not(&b"\t\r\n"[..])(rest)?;
+    let (rest, reference) = context("Invalid reference allele", is_not(&b"\t\r\n"[..]))(rest)?;
     record.reference.clear();
     record.reference.extend_from_slice(reference);
 
@@ -369,3 +367,110 @@ where
 
     Ok((rest, ()))
 }
+
+const COLUMNS: [&[u8]; 9] = [
+    b"CHROM", b"POS", b"ID", b"REF", b"ALT", b"QUAL", b"FILTER", b"INFO", b"FORMAT",
+];
+
+/// Build an error for a record line from an error returned by `parse_record`.
+/// The reason is taken from the parser context if there is one.
+pub fn record_parse_error(
+    line: &[u8],
+    line_num: u64,
+    error: nom::Err<nom::error::VerboseError<&[u8]>>,
+    header: &VCFHeader,
+) -> VCFError {
+    let (rest, context) = match &error {
+        nom::Err::Error(e) | nom::Err::Failure(e) => (
+            e.errors.first().map(|x| x.0).unwrap_or(&[]),
+            e.errors.iter().find_map(|x| match x.1 {
+                nom::error::VerboseErrorKind::Context(c) => Some(c),
+                _ => None,
+            }),
+        ),
+        nom::Err::Incomplete(_) => (&[][..], None),
+    };
+    record_error(line, line_num, line.len() - rest.len(), context, header)
+}
+
+/// Build an error for a record line which failed to parse at byte `offset`.
+/// Missing and empty columns are reported as such. Otherwise `reason` is used,
+/// or the reason is guessed from the column at `offset` if it is `None`.
+pub fn record_error(
+    line: &[u8],
+    line_num: u64,
+    offset: usize,
+    reason: Option<&'static str>,
+    header: &VCFHeader,
+) -> VCFError {
+    let line = line.strip_suffix(b"\n").unwrap_or(line);
+    let line = line.strip_suffix(b"\r").unwrap_or(line);
+    let offset = offset.min(line.len());
+    let index = line[..offset].iter().filter(|x| **x == b'\t').count();
+    let start = line[..offset]
+        .iter()
+        .rposition(|x| *x == b'\t')
+        .map(|x| x + 1)
+        .unwrap_or(0);
+    let end = line[offset..]
+        .iter()
+        .position(|x| *x == b'\t')
+        .map(|x| x + offset)
+        .unwrap_or(line.len());
+    let text = &line[start..end];
+    let detail = |field: &[u8], text: &[u8], reason| {
+        VCFError::RecordParseError(ParseErrorDetail::new(
+            line_num,
+            offset + 1,
+            field,
+            text,
+            reason,
+        ))
+    };
+
+    if offset == line.len() && index < 4 {
+        return detail(COLUMNS[index + 1], b"", "Missing column");
+    }
+    if text.is_empty() {
+        let field = COLUMNS.get(index).copied().unwrap_or(b"sample");
+        return detail(field, text, "Empty column");
+    }
+    if let (Some(reason), Some(field)) = (reason, COLUMNS.get(index)) {
+        return detail(field, text, reason);
+    }
+    match index {
+        0 => detail(b"CHROM", text, "Invalid chromosome"),
+        1 => detail(b"POS", text, "Position is not an integer"),
+        3 => detail(b"REF", text, "Invalid reference allele"),
+        5 => detail(b"QUAL", text, "Quality is not a number"),
+        7 => {
+            let entry_start = line[start..offset]
+                .iter()
+                .rposition(|x| *x == b';')
+                .map(|x| x + start + 1)
+                .unwrap_or(start);
+            let entry_end = line[offset..end]
+                .iter()
+                .position(|x| *x == b';')
+                .map(|x| x + offset)
+                .unwrap_or(end);
+            let entry = &line[entry_start..entry_end];
+            let key = entry.split(|x| *x == b'=').next().unwrap_or(b"");
+            if key.is_empty() {
+                return detail(b"INFO", entry, "INFO entry has no key");
+            }
+            let mut field = b"INFO/".to_vec();
+            field.extend_from_slice(key);
+            detail(&field, entry, "Invalid INFO entry")
+        }
+        x if x < COLUMNS.len() => detail(COLUMNS[x], text, "Unexpected character"),
+        x => {
+            let field = header
+                .samples()
+                .get(x - COLUMNS.len())
+                .map(|x| &x[..])
+                .unwrap_or(b"sample");
+            detail(field, text, "Unexpected character")
+        }
+    }
+}
@@ -0,0 +1,389 @@
+use super::value::{genotype_count, WRONG_NUMBER};
+use super::{Genotype, VCFRecord};
+use crate::{Number, U8Vec, VCFError};
+
+impl VCFRecord {
+    /// Split a multi-allelic record into biallelic records like `bcftools norm -m-`.
+    /// INFO and FORMAT values of `Number=A`, `R` and `G` are subset for each
+    /// alternative allele. Other alternative alleles in `GT` become the reference allele.
+    pub fn split_alleles(&self) -> Result<Vec<VCFRecord>, VCFError> {
+        if self.alternative.len() <= 1 {
+            return Ok(vec![self.clone()]);
+        }
+        let allele_count = self.alternative.len() + 1;
+
+        (1..allele_count)
+            .map(|allele| {
+                let mut record = self.clone();
+                record.parse_genotype();
+                record.alternative = vec![self.alternative[allele - 1].clone()];
+
+                for (key, values) in record.info.iter_mut() {
+                    if let Some(definition) = self.header.info(key) {
+                        *values =
+                            subset_values(key, values, definition.number, allele_count, allele, 2)?;
+                    }
+                }
+
+                for sample in record.genotype.iter_mut() {
+                    let ploidy = self
+                        .format
+                        .iter()
+                        .zip(sample.iter())
+                        .find(|(key, _)| *key == b"GT")
+                        .and_then(|(_, values)| values.first())
+                        .map(|x| x.split(|y| *y == b'/' || *y == b'|').count())
+                        .unwrap_or(2);
+                    for (key, values) in self.format.iter().zip(sample.iter_mut()) {
+                        if key == b"GT" {
+                            for one in values.iter_mut() {
+                                *one = split_genotype(one, allele)?;
+                            }
+                        } else if let Some(definition) = self.header.format(key) {
+                            *values = subset_values(
+                                key,
+                                values,
+                                definition.number,
+                                allele_count,
+                                allele,
+                                ploidy,
+                            )?;
+                        }
+                    }
+                }
+
+                Ok(record)
+            })
+            .collect()
+    }
+
+    /// Join consecutive records at the same position with the same reference allele
+    /// into multi-allelic records like `bcftools norm -m+`. Values of `Number=A`, `R`
+    /// and `G` are rebuilt for the joined alleles and `GT` is recoded.
+    pub fn join_alleles(records: &[VCFRecord]) -> Result<Vec<VCFRecord>, VCFError> {
+        let mut joined = Vec::new();
+        let mut begin = 0;
+        for i in 1..=records.len() {
+            if i == records.len() || !is_same_site(&records[begin], &records[i]) {
+                joined.push(join_site(&records[begin..i])?);
+                begin = i;
+            }
+        }
+        Ok(joined)
+    }
+}
+
+fn is_same_site(a: &VCFRecord, b: &VCFRecord) -> bool {
+    a.chromosome == b.chromosome && a.position == b.position && a.reference == b.reference
+}
+
+fn join_site(records: &[VCFRecord]) -> Result<VCFRecord, VCFError> {
+    let mut joined = records[0].clone();
+    if records.len() == 1 {
+        return Ok(joined);
+    }
+
+    // Allele index in the joined record for each allele of each record
+    joined.alternative.clear();
+    let mut allele_maps = Vec::new();
+    for record in records {
+        let mut allele_map = vec![0];
+        for one in record.alternative.iter() {
+            match joined.alternative.iter().position(|x| x == one) {
+                Some(x) => allele_map.push(x + 1),
+                None => {
+                    joined.alternative.push(one.clone());
+                    allele_map.push(joined.alternative.len());
+                }
+            }
+        }
+        allele_maps.push(allele_map);
+    }
+    let allele_count = joined.alternative.len() + 1;
+
+    for record in records[1..].iter() {
+        for one in record.id.iter() {
+            if !joined.id.contains(one) {
+                joined.id.push(one.clone());
+            }
+        }
+        for one in record.filter.iter() {
+            if !joined.filter.contains(one) {
+                joined.filter.push(one.clone());
+            }
+        }
+        joined.qual = match (joined.qual, record.qual) {
+            (Some(x), Some(y)) => Some(x.max(y)),
+            (x, y) => x.or(y),
+        };
+    }
+    if joined.filter.len() > 1 {
+        joined.filter.retain(|x| x != b"PASS");
+    }
+
+    let header = records[0].header();
+    joined.info = union_keys(records.iter().map(|x| x.info.iter().map(|y| &y.0)))
+        .into_iter()
+        .map(|key| {
+            let values: Vec<_> = records.iter().map(|x| x.info(&key)).collect();
+            let number = header.info(&key).map(|x| x.number);
+            let values = join_values(&key, &values, number, &allele_maps, allele_count, 2)?;
+            Ok((key, values))
+        })
+        .collect::<Result<_, VCFError>>()?;
+
+    let mut format = union_keys(records.iter().map(|x| x.format.iter()));
+    if let Some(x) = format.iter().position(|x| x == b"GT") {
+        let gt = format.remove(x);
+        format.insert(0, gt);
+    }
+    let sample_count = records
+        .iter()
+        .map(|x| x.sample_genotype_count())
+        .max()
+        .unwrap_or(0);
+    joined.genotype = (0..sample_count)
+        .map(|sample| {
+            let mut joined_sample: Vec<Vec<U8Vec>> = Vec::new();
+            let mut ploidy = 2;
+            for key in format.iter() {
+                let values: Vec<_> = records
+                    .iter()
+                    .map(|x| {
+                        x.format_index
+                            .get(key)
+                            .and_then(|y| x.sample_genotype(sample).and_then(|z| z.get(*y)))
+                    })
+                    .collect();
+                if key == b"GT" {
+                    let genotype = join_genotypes(&values, &allele_maps)?;
+                    if let Some(x) = genotype.as_ref() {
+                        ploidy = x.ploidy();
+                    }
+                    joined_sample.push(vec![genotype
+                        .map(|x| x.to_bytes())
+                        .unwrap_or_else(|| b".".to_vec())]);
+                } else {
+                    let number = header.format(key).map(|x| x.number);
+                    joined_sample.push(join_values(
+                        key,
+                        &values,
+                        number,
+                        &allele_maps,
+                        allele_count,
+                        ploidy,
+                    )?);
+                }
+            }
+            Ok(joined_sample)
+        })
+        .collect::<Result<_, VCFError>>()?;
+    joined.format = format;
+    joined.recreate_info_and_genotype_index();
+
+    Ok(joined)
+}
+
+/// Keys in the order of appearance.
+fn union_keys<'a>(keys: impl Iterator<Item = impl Iterator<Item = &'a U8Vec>>) -> Vec<U8Vec> {
+    let mut union: Vec<U8Vec> = Vec::new();
+    for one in keys.flatten() {
+        if !union.contains(one) {
+            union.push(one.clone());
+        }
+    }
+    union
+}
+
+/// Join values of records. The first value is used if `number` is not `A`, `R` nor `G`.
+fn join_values(
+    key: &[u8],
+    values: &[Option<&Vec<U8Vec>>],
+    number: Option<&Number>,
+    allele_maps: &[Vec<usize>],
+    allele_count: usize,
+    ploidy: usize,
+) -> Result<Vec<U8Vec>, VCFError> {
+    let count = |x: usize| match number {
+        Some(Number::Allele) => Some(x - 1),
+        Some(Number::Reference) => Some(x),
+        Some(Number::Genotype) => genotype_count(x, ploidy),
+        _ => None,
+    };
+    let joined_count = match count(allele_count) {
+        Some(x) => x,
+        None if number == Some(&Number::Genotype) => {
+            return Err(VCFError::TypedValueError(key.to_vec(), WRONG_NUMBER))
+        }
+        None => {
+            return Ok(values
+                .iter()
+                .flatten()
+                .next()
+                .map(|x| x.to_vec())
+                .unwrap_or_else(|| vec![b".".to_vec()]))
+        }
+    };
+
+    let mut joined: Vec<Option<&U8Vec>> = vec![None; joined_count];
+    for (one, allele_map) in values.iter().zip(allele_maps.iter()) {
+        let one = match one {
+            Some(x) if x.len() != 1 || x[0] != b"." => x,
+            _ => continue,
+        };
+        if Some(one.len()) != count(allele_map.len()) {
+            return Err(VCFError::TypedValueError(key.to_vec(), WRONG_NUMBER));
+        }
+        let indexes: Vec<usize> = match number {
+            Some(Number::Allele) => allele_map[1..].iter().map(|x| x - 1).collect(),
+            Some(Number::Reference) => allele_map.clone(),
+            _ => genotypes(ploidy, allele_map.len())
+                .into_iter()
+                .map(|x| {
+                    let mut alleles: Vec<usize> = x.iter().map(|y| allele_map[*y]).collect();
+                    alleles.sort_unstable();
+                    genotype_index(&alleles)
+                })
+                .collect(),
+        };
+        for (value, index) in one.iter().zip(indexes) {
+            if joined[index].is_none() {
+                joined[index] = Some(value);
+            }
+        }
+    }
+    Ok(joined
+        .into_iter()
+        .map(|x| x.cloned().unwrap_or_else(|| b".".to_vec()))
+        .collect())
+}
+
+/// Merge alternative alleles of each record into one genotype.
+fn join_genotypes(
+    values: &[Option<&Vec<U8Vec>>],
+    allele_maps: &[Vec<usize>],
+) -> Result<Option<Genotype>, VCFError> {
+    let mut joined: Option<(Vec<Option<usize>>, Vec<bool>)> = None;
+    for (one, allele_map) in values.iter().zip(allele_maps.iter()) {
+        let genotype = match one.and_then(|x| x.first()) {
+            Some(x) => Genotype::from_bytes(x)?,
+            None => continue,
+        };
+        let alleles = genotype
+            .alleles()
+            .iter()
+            .map(|x| match x {
+                Some(y) => allele_map.get(*y).map(|z| Some(*z)).ok_or_else(|| {
+                    VCFError::TypedValueError(b"GT".to_vec(), "Invalid allele index")
+                }),
+                None => Ok(None),
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+        let (joined_alleles, phasing) = match joined.as_mut() {
+            Some(x) => x,
+            None => {
+                joined = Some((alleles, genotype.phasing().to_vec()));
+                continue;
+            }
+        };
+        if joined_alleles.len() != alleles.len() {
+            continue;
+        }
+        let is_phased = phasing.iter().any(|x| *x);
+        // Alternative alleles are placed at the same position if phased,
+        // otherwise at any reference or missing allele
+        let is_ref = |x: &Option<usize>| x.unwrap_or(0) == 0;
+        for (i, one) in alleles.iter().enumerate() {
+            if is_ref(one) {
+                continue;
+            }
+            if is_phased {
+                if is_ref(&joined_alleles[i]) {
+                    joined_alleles[i] = *one;
+                }
+            } else if let Some(x) = joined_alleles.iter_mut().find(|x| is_ref(x)) {
+                *x = *one;
+            }
+        }
+    }
+    joined
+        .map(|(mut alleles, phasing)| {
+            if phasing.iter().all(|x| !x) && alleles.iter().all(|x| x.is_some()) {
+                alleles.sort_unstable();
+            }
+            Genotype::from_parts(alleles, phasing)
+        })
+        .transpose()
+}
+
+/// Remap `allele` to 1 and other alternative alleles to 0.
+fn split_genotype(value: &[u8], allele: usize) -> Result<U8Vec, VCFError> {
+    let genotype = Genotype::from_bytes(value)?;
+    let alleles = genotype
+        .alleles()
+        .iter()
+        .map(|x| x.map(|y| if y == allele { 1 } else { 0 }))
+        .collect();
+    Ok(Genotype::from_parts(alleles, genotype.phasing().to_vec())?.to_bytes())
+}
+
+fn subset_values(
+    key: &[u8],
+    values: &[U8Vec],
+    number: &Number,
+    allele_count: usize,
+    allele: usize,
+    ploidy: usize,
+) -> Result<Vec<U8Vec>, VCFError> {
+    // A single "." means that all values are missing
+    if values.len() == 1 && values[0] == b"." {
+        return Ok(values.to_vec());
+    }
+    let (expected, indexes) = match number {
+        Number::Allele => (allele_count - 1, vec![allele - 1]),
+        Number::Reference => (allele_count, vec![0, allele]),
+        Number::Genotype => (
+            genotype_count(allele_count, ploidy)
+                .ok_or_else(|| VCFError::TypedValueError(key.to_vec(), WRONG_NUMBER))?,
+            (0..=ploidy)
+                .map(|x| {
+                    let mut alleles = vec![0; ploidy - x];
+                    alleles.resize(ploidy, allele);
+                    genotype_index(&alleles)
+                })
+                .collect(),
+        ),
+        _ => return Ok(values.to_vec()),
+    };
+    if values.len() != expected {
+        return Err(VCFError::TypedValueError(key.to_vec(), WRONG_NUMBER));
+    }
+    Ok(indexes.into_iter().map(|x| values[x].clone()).collect())
+}
+
+/// Index of the genotype in the order defined by VCF specification.
+/// `alleles` must be sorted, and the genotype count of them must not overflow.
+fn genotype_index(alleles: &[usize]) -> usize {
+    alleles
+        .iter()
+        .enumerate()
+        .map(|(i, x)| genotype_count(*x, i + 1).unwrap_or_default())
+        .sum()
+}
+
+/// All genotypes of `ploidy` in the order defined by VCF specification.
+fn genotypes(ploidy: usize, allele_count: usize) -> Vec<Vec<usize>> {
+    if ploidy == 0 {
+        return vec![Vec::new()];
+    }
+    (0..allele_count)
+        .flat_map(|last| {
+            genotypes(ploidy - 1, last + 1)
+                .into_iter()
+                .map(move |mut x| {
+                    x.push(last);
+                    x
+                })
+        })
+        .collect()
+}
@@ -1,11 +1,11 @@
 use super::*;
-use crate::VCFError;
+use crate::{ParseErrorDetail, VCFError};
 use nom::{self, bytes::complete::is_not, bytes::complete::tag, bytes::complete::take_while};
 
 fn create_header() -> VCFHeader {
     let vcf_data = include_bytes!("../../testfiles/simple1.vcf");
     let mut vcf_reader = io::BufReader::new(&vcf_data[..]);
-    let (_, _, header) = crate::header::parse_header(&mut vcf_reader).unwrap();
+    let (_, header) = crate::header::parse_header(&mut vcf_reader).unwrap();
     header
 }
 
@@ -109,10 +109,7 @@ fn test_parse_info() {
 fn test_parse_record1() -> Result<(), VCFError> {
     let test_record1 = &b"13\t32889968\trs206119,rs60320776\tG\tA\t25743.5\t.\tAC=54;AF=1;AN=54;DP=749\tGT:AD:DP\t1/1:0,14:14\t1/1:0,19:19\n"[..];
     let mut record = VCFRecord::new(create_header());
-    assert_eq!(
-        parse_record::<nom::error::VerboseError<_>>(&test_record1, &mut record),
-        Ok((&b""[..], ()))
-    );
+    assert_eq!(parse_record(test_record1, &mut record), Ok((&b""[..], ())));
 
     let mut record2 = record.clone();
     record2.parse_bytes(test_record1, 1)?;
@@ -304,10 +301,7 @@ fn test_parse_record1() -> Result<(), VCFError> {
 fn test_parse_record2() -> Result<(), VCFError> {
     let test_record1 = &b"13\t32889968\trs206119,rs60320776\tG\tA"[..];
     let mut record = VCFRecord::new(create_header());
-    assert_eq!(
-        parse_record::<nom::error::VerboseError<_>>(&test_record1, &mut record),
-        Ok((&b""[..], ()))
-    );
+    assert_eq!(parse_record(test_record1, &mut record), Ok((&b""[..], ())));
     assert_eq!(record.chromosome, b"13");
     assert_eq!(record.position, 32889968);
     assert_eq!(record.id, vec![&b"rs206119"[..], &b"rs60320776"[..]]);
@@ -322,10 +316,7 @@ fn test_parse_record3() -> Result<(), VCFError> {
     let test_record1 = &b"13\t32889968\trs206119,rs60320776\tG\tA\t25743.5\r\n"[..];
     let mut record = VCFRecord::new(create_header());
 
-    assert_eq!(
-        parse_record::<nom::error::VerboseError<_>>(&test_record1, &mut record),
-        Ok((&b""[..], ()))
-    );
+    assert_eq!(parse_record(test_record1, &mut record), Ok((&b""[..], ())));
     assert_eq!(record.chromosome, b"13");
     assert_eq!(record.position, 32889968);
     assert_eq!(record.id, vec![&b"rs206119"[..], &b"rs60320776"[..]]);
@@ -341,10 +332,7 @@ fn test_parse_record3_1() -> Result<(), VCFError> {
     let test_record1 = &b"13\t32889968\trs206119,rs60320776\tG\tA\t8.39728e+06\r\n"[..];
     let mut record = VCFRecord::new(create_header());
 
-    assert_eq!(
-        parse_record::<nom::error::VerboseError<_>>(&test_record1, &mut record),
-        Ok((&b""[..], ()))
-    );
+    assert_eq!(parse_record(test_record1, &mut record), Ok((&b""[..], ())));
     assert_eq!(record.chromosome, b"13");
     assert_eq!(record.position, 32889968);
     assert_eq!(record.id, vec![&b"rs206119"[..], &b"rs60320776"[..]]);
@@ -360,10 +348,7 @@ fn test_parse_record4() -> Result<(), VCFError> {
     let test_record1 = &b"13\t32889968\trs206119,rs60320776\tG\tA\t25743.5\tPASS"[..];
     let mut record = VCFRecord::new(create_header());
 
-    assert_eq!(
-        parse_record::<nom::error::VerboseError<_>>(&test_record1, &mut record),
-        Ok((&b""[..], ()))
-    );
+    assert_eq!(parse_record(test_record1, &mut record), Ok((&b""[..], ())));
     assert_eq!(record.chromosome, b"13");
     assert_eq!(record.position, 32889968);
     assert_eq!(record.id, vec![&b"rs206119"[..], &b"rs60320776"[..]]);
@@ -381,10 +366,7 @@ fn test_parse_record5() -> Result<(), VCFError> {
         &b"13\t32889968\trs206119,rs60320776\tG\tA\t25743.5\t.\tAC=54;AF=1;AN=54;DP=749\n"[..];
     let mut record = VCFRecord::new(create_header());
 
-    assert_eq!(
-        parse_record::<nom::error::VerboseError<_>>(&test_record1, &mut record),
-        Ok((&b""[..], ()))
-    );
+    assert_eq!(parse_record(test_record1, &mut record), Ok((&b""[..], ())));
     assert_eq!(record.chromosome, b"13");
     assert_eq!(record.position, 32889968);
     assert_eq!(record.id, vec![&b"rs206119"[..], &b"rs60320776"[..]]);
@@ -410,10 +392,7 @@ fn test_parse_record6() -> Result<(), VCFError> {
     let test_record1 = &b"13\t32889968\t.\tG\t.\t.\t.\tAC=54;AF=1;AN=54;DP=749\tGT:AD:DP"[..];
     let mut record = VCFRecord::new(create_header());
 
-    assert_eq!(
-        parse_record::<nom::error::VerboseError<_>>(&test_record1, &mut record),
-        Ok((&b""[..], ()))
-    );
+    assert_eq!(parse_record(test_record1, &mut record), Ok((&b""[..], ())));
     assert_eq!(record.chromosome, b"13");
     assert_eq!(record.position, 32889968);
     assert_eq!(record.id, Vec::<&[u8]>::new());
@@ -441,10 +420,7 @@ fn test_parse_record7() -> Result<(), VCFError> {
     let test_record1 = &b"13\t32889968\trs206119,rs60320776\tG\tA\t25743.5\t.\tAC=54;AF=1;AN=54;DP=749\tGT:AD:DP\t1/1:0,14:14\t1/1:0,19:19\n"[..];
     let mut record = VCFRecord::new(create_header());
 
-    assert_eq!(
-        parse_record::<nom::error::VerboseError<_>>(&test_record1, &mut record),
-        Ok((&b""[..], ()))
-    );
+    assert_eq!(parse_record(test_record1, &mut record), Ok((&b""[..], ())));
     assert_eq!(record.chromosome, b"13");
     assert_eq!(record.position, 32889968);
     assert_eq!(record.id, vec![&b"rs206119"[..], &b"rs60320776"[..]]);
@@ -482,10 +458,7 @@ fn test_parse_record7() -> Result<(), VCFError> {
     assert_eq!(record.info(b"XX"), None);
 
     let test_record1 = &b"13\t32889968\trs206119,rs60320776\tG\tA"[..];
-    assert_eq!(
-        parse_record::<nom::error::VerboseError<_>>(&test_record1, &mut record),
-        Ok((&b""[..], ()))
-    );
+    assert_eq!(parse_record(test_record1, &mut record), Ok((&b""[..], ())));
     assert_eq!(record.chromosome, b"13");
     assert_eq!(record.position, 32889968);
     assert_eq!(record.id, vec![&b"rs206119"[..], &b"rs60320776"[..]]);
@@ -501,10 +474,7 @@ fn test_parse_record7() -> Result<(), VCFError> {
     assert_eq!(record.info(b"AF"), None);
 
     let test_record1 = &b"13\t32889968\trs206119,rs60320776\tG\tA\t25743.5\r\n"[..];
-    assert_eq!(
-        parse_record::<nom::error::VerboseError<_>>(&test_record1, &mut record),
-        Ok((&b""[..], ()))
-    );
+    assert_eq!(parse_record(test_record1, &mut record), Ok((&b""[..], ())));
     assert_eq!(record.chromosome, b"13");
     assert_eq!(record.position, 32889968);
     assert_eq!(record.id, vec![&b"rs206119"[..], &b"rs60320776"[..]]);
@@ -514,10 +484,7 @@ fn test_parse_record7() -> Result<(), VCFError> {
 
     let test_record1 = &b"13\t32889968\trs206119,rs60320776\tG\tA\t25743.5\tPASS"[..];
 
-    assert_eq!(
-        parse_record::<nom::error::VerboseError<_>>(&test_record1, &mut record),
-        Ok((&b""[..], ()))
-    );
+    assert_eq!(parse_record(test_record1, &mut record), Ok((&b""[..], ())));
     assert_eq!(record.chromosome, b"13");
     assert_eq!(record.position, 32889968);
     assert_eq!(record.id, vec![&b"rs206119"[..], &b"rs60320776"[..]]);
@@ -529,10 +496,7 @@ fn test_parse_record7() -> Result<(), VCFError> {
     let test_record1 =
         &b"13\t32889968\trs206119,rs60320776\tG\tA\t25743.5\t.\tAC=54;AF=1;AN=54;DP=749\n"[..];
 
-    assert_eq!(
-        parse_record::<nom::error::VerboseError<_>>(&test_record1, &mut record),
-        Ok((&b""[..], ()))
-    );
+    assert_eq!(parse_record(test_record1, &mut record), Ok((&b""[..], ())));
     assert_eq!(record.chromosome, b"13");
     assert_eq!(record.position, 32889968);
     assert_eq!(record.id, vec![&b"rs206119"[..], &b"rs60320776"[..]]);
@@ -555,10 +519,7 @@ fn test_parse_record7() -> Result<(), VCFError> {
 
     let test_record1 = &b"13\t32889968\t.\tG\t.\t.\t.\tAC=54;AF=1;AN=54;DP=749\tGT:AD:DP"[..];
 
-    assert_eq!(
-        parse_record::<nom::error::VerboseError<_>>(&test_record1, &mut record),
-        Ok((&b""[..], ()))
-    );
+    assert_eq!(parse_record(test_record1, &mut record), Ok((&b""[..], ())));
     assert_eq!(record.chromosome, b"13");
     assert_eq!(record.position, 32889968);
     assert_eq!(record.id, Vec::<&[u8]>::new());
@@ -579,10 +540,7 @@ fn test_parse_record7() -> Result<(), VCFError> {
 
     let test_record1 = &b"13\t32889968\t.\tG\t.\t.\t.\t.\n"[..];
 
-    assert_eq!(
-        parse_record::<nom::error::VerboseError<_>>(&test_record1, &mut record),
-        Ok((&b""[..], ()))
-    );
+    assert_eq!(parse_record(test_record1, &mut record), Ok((&b""[..], ())));
     assert_eq!(record.chromosome, b"13");
     assert_eq!(record.position, 32889968);
     assert_eq!(record.id, Vec::<&[u8]>::new());
@@ -598,15 +556,706 @@ fn test_parse_record7() -> Result<(), VCFError> {
 fn test_write() {
     let mut vcf_record = VCFRecord::new(create_header());
     let test_record1 = &b"13\t32889968\t.\tG\tA\t.\t.\tAC=1;AF=0.5\n"[..];
-    parse_record::<nom::error::VerboseError<_>>(test_record1, &mut vcf_record).unwrap();
+    parse_record(test_record1, &mut vcf_record).unwrap();
     let mut write_data = Vec::new();
     vcf_record.write_record(&mut write_data).unwrap();
     assert_eq!(write_data, test_record1);
 
     let test_record2 =
         &b"13\t32889968\t123\tG\tA\t102.0\tLOW,HIGH\tAC=1;AF=0.5\tGT:DP\t1/1:10\t0/1:20\n"[..];
-    parse_record::<nom::error::VerboseError<_>>(test_record2, &mut vcf_record).unwrap();
+    parse_record(test_record2, &mut vcf_record).unwrap();
     let mut write_data = Vec::new();
     vcf_record.write_record(&mut write_data).unwrap();
     assert_eq!(write_data, test_record2);
 }
+
+fn create_typed_header() -> VCFHeader {
+    let vcf_data = &b"##fileformat=VCFv4.2
+##INFO=<ID=DB,Number=0,Type=Flag,Description=\"dbSNP membership\">
+##INFO=<ID=AC,Number=A,Type=Integer,Description=\"Allele count\">
+##INFO=<ID=AF,Number=A,Type=Float,Description=\"Allele Frequency\">
+##INFO=<ID=RC,Number=R,Type=Integer,Description=\"Read count for each allele\">
+##INFO=<ID=DP,Number=1,Type=Integer,Description=\"Depth\">
+##INFO=<ID=ST,Number=1,Type=Character,Description=\"Strand\">
+##INFO=<ID=GENE,Number=.,Type=String,Description=\"Gene name\">
+##FORMAT=<ID=GT,Number=1,Type=String,Description=\"Genotype\">
+##FORMAT=<ID=AD,Number=R,Type=Integer,Description=\"Allelic depths\">
+##FORMAT=<ID=DP,Number=1,Type=Integer,Description=\"Depth\">
+##FORMAT=<ID=GQ,Number=1,Type=Integer,Description=\"Genotype quality\">
+##FORMAT=<ID=PL,Number=G,Type=Integer,Description=\"Genotype likelihoods\">
+##FORMAT=<ID=VAF,Number=A,Type=Float,Description=\"Variant allele fraction\">
+##FORMAT=<ID=FT,Number=1,Type=String,Description=\"Filter\">
+#CHROM\tPOS\tID\tREF\tALT\tQUAL\tFILTER\tINFO\tFORMAT\tS1\tS2\tS3
+"[..];
+    let (_, header) = crate::header::parse_header(&mut &vcf_data[..]).unwrap();
+    header
+}
+
+#[test]
+fn test_info_value() -> Result<(), VCFError> {
+    let record = VCFRecord::from_bytes(
+        b"1\t100\t.\tA\tC,G\t.\t.\tDB;AC=1,.;AF=0.5,1e-3;RC=10,5,2;DP=.;ST=+;GENE=ABC,.;XX=1",
+        1,
+        create_typed_header(),
+    )?;
+    assert_eq!(record.info_value(b"DB")?, Some(InfoValue::Flag));
+    assert_eq!(
+        record.info_value(b"AC")?,
+        Some(InfoValue::Integer(vec![Some(1), None]))
+    );
+    assert_eq!(
+        record.info_value(b"AF")?,
+        Some(InfoValue::Float(vec![Some(0.5), Some(0.001)]))
+    );
+    assert_eq!(
+        record.info_value(b"RC")?,
+        Some(InfoValue::Integer(vec![Some(10), Some(5), Some(2)]))
+    );
+    assert_eq!(
+        record.info_value(b"DP")?,
+        Some(InfoValue::Integer(vec![None]))
+    );
+    assert_eq!(
+        record.info_value(b"ST")?,
+        Some(InfoValue::Character(vec![Some(b'+')]))
+    );
+    assert_eq!(
+        record.info_value(b"GENE")?,
+        Some(InfoValue::String(vec![Some(b"ABC".to_vec()), None]))
+    );
+    assert_eq!(record.info_value(b"END")?, None);
+    assert!(record.info_value(b"XX").is_err());
+
+    for one in [
+        &b"1\t100\t.\tA\tC,G\t.\t.\tDB=1"[..],
+        &b"1\t100\t.\tA\tC,G\t.\t.\tAC=1"[..],
+        &b"1\t100\t.\tA\tC,G\t.\t.\tAC=1,X"[..],
+        &b"1\t100\t.\tA\tC,G\t.\t.\tAF=0.1,X"[..],
+        &b"1\t100\t.\tA\tC,G\t.\t.\tRC=1,2"[..],
+        &b"1\t100\t.\tA\tC,G\t.\t.\tDP=1,2"[..],
+        &b"1\t100\t.\tA\tC,G\t.\t.\tST=AB"[..],
+    ]
+    .iter()
+    {
+        let record = VCFRecord::from_bytes(one, 1, create_typed_header())?;
+        let key = &record.info[0].0;
+        assert!(record.info_value(key).is_err());
+    }
+    Ok(())
+}
+
+#[test]
+fn test_genotype_value() -> Result<(), VCFError> {
+    let record = VCFRecord::from_bytes(
+        b"1\t100\t.\tA\tC\t.\t.\t.\tGT:AD:DP:PL:VAF:FT\t0/1:10,5:15:100,0,200:0.33:PASS\t1:.:3:0,30\t./.:2,0",
+        1,
+        create_typed_header(),
+    )?;
+    assert_eq!(
+        record.genotype_value(b"S1", b"AD")?,
+        Some(FormatValue::Integer(vec![Some(10), Some(5)]))
+    );
+    assert_eq!(
+        record.genotype_value(b"S1", b"PL")?,
+        Some(FormatValue::Integer(vec![Some(100), Some(0), Some(200)]))
+    );
+    // PL of haploid call has a value for each allele
+    assert_eq!(
+        record.genotype_value(b"S2", b"PL")?,
+        Some(FormatValue::Integer(vec![Some(0), Some(30)]))
+    );
+    assert_eq!(
+        record.genotype_value(b"S1", b"VAF")?,
+        Some(FormatValue::Float(vec![Some(0.33)]))
+    );
+    assert_eq!(
+        record.genotype_value(b"S1", b"FT")?,
+        Some(FormatValue::String(vec![Some(b"PASS".to_vec())]))
+    );
+    assert_eq!(
+        record.genotype_value(b"S3", b"FT")?,
+        Some(FormatValue::String(vec![None]))
+    );
+    assert_eq!(record.genotype_value(b"S4", b"DP")?, None);
+    assert_eq!(record.genotype_value(b"S1", b"GQ")?, None);
+
+    assert_eq!(
+        record.genotype_integer_column(b"DP")?,
+        vec![Some(15), Some(3), None]
+    );
+    assert_eq!(
+        record.genotype_integer_column(b"GQ")?,
+        vec![None, None, None]
+    );
+    assert_eq!(
+        record.genotype_float_column(b"VAF")?,
+        vec![Some(0.33), None, None]
+    );
+    assert!(record.genotype_integer_column(b"VAF").is_err());
+    assert!(record.genotype_integer_column(b"XX").is_err());
+
+    let column = record.genotype_column(b"AD")?.unwrap();
+    assert_eq!(column.len(), 3);
+    assert_eq!(column[1].integers(), Some(&[None][..]));
+    assert_eq!(column[2].integers(), Some(&[Some(2), Some(0)][..]));
+    assert_eq!(record.genotype_column(b"GQ")?, None);
+
+    let record = VCFRecord::from_bytes(
+        b"1\t100\t.\tA\tC\t.\t.\t.\tGT:AD:PL\t0/1:10:0,1,2\t0/1:10,5:0,1\t0/1:1,2:0,1,2",
+        1,
+        create_typed_header(),
+    )?;
+    assert!(record.genotype_value(b"S1", b"AD").is_err());
+    assert!(record.genotype_value(b"S2", b"PL").is_err());
+    assert!(record.genotype_column(b"AD").is_err());
+    assert!(record.genotype_value(b"S3", b"AD").is_ok());
+    Ok(())
+}
+
+#[test]
+fn test_genotype_count_overflow() -> Result<(), VCFError> {
+    assert_eq!(super::value::genotype_count(3, 2), Some(6));
+    assert_eq!(super::value::genotype_count(100, 64), None);
+
+    let alternative = (1..100).map(|x| x.to_string()).collect::<Vec<_>>();
+    let genotype = vec!["0"; 64];
+    let line = format!(
+        "1\t100\t.\tA\t{}\t.\t.\t.\tGT:PL\t{}:0",
+        alternative.join(","),
+        genotype.join("/")
+    );
+    let record = VCFRecord::from_bytes(line.as_bytes(), 1, create_typed_header())?;
+    assert_eq!(
+        record.genotype_value(b"S1", b"PL")?,
+        Some(FormatValue::Integer(vec![Some(0)]))
+    );
+    assert_eq!(
+        record.validate(),
+        vec![Violation::UnknownContig(b"1".to_vec())]
+    );
+    Ok(())
+}
+
+#[test]
+fn test_genotype_parse() -> Result<(), VCFError> {
+    let genotype = Genotype::from_bytes(b"0/1")?;
+    assert_eq!(genotype.alleles(), &[Some(0), Some(1)]);
+    assert_eq!(genotype.phasing(), &[false]);
+    assert_eq!(genotype.ploidy(), 2);
+    assert!(genotype.is_het());
+    assert!(!genotype.is_phased());
+    assert!(!genotype.is_hom_ref());
+    assert!(!genotype.is_hom_alt());
+    assert!(!genotype.is_missing());
+
+    let genotype: Genotype = "1|12".parse()?;
+    assert_eq!(genotype.alleles(), &[Some(1), Some(12)]);
+    assert!(genotype.is_phased());
+    assert!(genotype.is_het());
+
+    let genotype = Genotype::from_bytes(b"./.")?;
+    assert_eq!(genotype.alleles(), &[None, None]);
+    assert!(genotype.is_missing());
+    assert!(!genotype.is_het());
+    assert!(!genotype.is_hom_ref());
+
+    let genotype = Genotype::from_bytes(b"1")?;
+    assert_eq!(genotype.ploidy(), 1);
+    assert!(genotype.is_hom_alt());
+    assert!(!genotype.is_phased());
+
+    let genotype = Genotype::from_bytes(b"0/0|0")?;
+    assert_eq!(genotype.phasing(), &[false, true]);
+    assert!(genotype.is_hom_ref());
+    assert!(!genotype.is_phased());
+
+    let genotype = Genotype::from_bytes(b"2/2/.")?;
+    assert!(!genotype.is_hom_alt());
+    assert!(!genotype.is_het());
+    assert!(!genotype.is_missing());
+    assert!(!Genotype::from_bytes(b"0/.")?.is_het());
+    assert!(Genotype::from_bytes(b"1/2")?.is_het());
+    assert!(!Genotype::from_bytes(b"1/2")?.is_hom_alt());
+
+    for one in [&b""[..], b"0/", b"A/1", b"-1/0", b"0\\1", b"+1"].iter() {
+        assert!(Genotype::from_bytes(one).is_err());
+    }
+
+    for one in [&b"0/1"[..], b"1|0", b"./.", b".", b"0/1|2", b"10|.", b"1"].iter() {
+        assert_eq!(Genotype::from_bytes(one)?.to_bytes(), one.to_vec());
+    }
+    assert_eq!(
+        Genotype::new(vec![Some(0), None, Some(2)], true).to_string(),
+        "0|.|2"
+    );
+    assert_eq!(
+        Genotype::from_parts(vec![Some(0), Some(1), Some(2)], vec![true, false])?.to_string(),
+        "0|1/2"
+    );
+    assert!(Genotype::from_parts(vec![Some(0), Some(1)], vec![]).is_err());
+    assert!(Genotype::from_parts(vec![], vec![true]).is_err());
+    Ok(())
+}
+
+#[test]
+fn test_genotype_call() -> Result<(), VCFError> {
+    let mut record = VCFRecord::from_bytes(
+        b"1\t100\t.\tA\tC\t.\t.\t.\tGT:DP\t0/1:10\t1|1:3\t.",
+        1,
+        create_typed_header(),
+    )?;
+    assert_eq!(
+        record.genotype_call(b"S1")?,
+        Some(Genotype::from_bytes(b"0/1")?)
+    );
+    assert!(record.genotype_call(b"S2")?.unwrap().is_hom_alt());
+    assert!(record.genotype_call(b"S3")?.unwrap().is_missing());
+    assert_eq!(record.genotype_call(b"S4")?, None);
+
+    assert!(record.set_genotype_call(b"S3", &Genotype::new(vec![Some(0), Some(1)], true)));
+    assert!(!record.set_genotype_call(b"S4", &Genotype::new(vec![Some(0)], false)));
+    let mut line = Vec::new();
+    record.write_record(&mut line)?;
+    assert_eq!(
+        line,
+        b"1\t100\t.\tA\tC\t.\t.\t.\tGT:DP\t0/1:10\t1|1:3\t0|1\n".to_vec()
+    );
+
+    let mut record = VCFRecord::from_bytes(
+        b"1\t100\t.\tA\tC\t.\t.\t.\tDP\t10\t3\t5",
+        1,
+        create_typed_header(),
+    )?;
+    assert_eq!(record.genotype_call(b"S1")?, None);
+    assert!(record.set_genotype_call(b"S2", &Genotype::from_bytes(b"0/1")?));
+    let mut line = Vec::new();
+    record.write_record(&mut line)?;
+    assert_eq!(
+        line,
+        b"1\t100\t.\tA\tC\t.\t.\t.\tGT:DP\t.:10\t0/1:3\t.:5\n".to_vec()
+    );
+    assert_eq!(
+        record.genotype_integer_column(b"DP")?,
+        vec![Some(10), Some(3), Some(5)]
+    );
+
+    let mut record = VCFRecord::from_bytes(b"1\t100\t.\tA\tC\t.\t.\t.", 1, create_typed_header())?;
+    assert!(record.set_genotype_call(b"S1", &Genotype::from_bytes(b"1/1")?));
+    let mut line = Vec::new();
+    record.write_record(&mut line)?;
+    assert_eq!(line, b"1\t100\t.\tA\tC\t.\t.\t.\tGT\t1/1\t.\t.\n".to_vec());
+    Ok(())
+}
+
+#[test]
+fn test_split_alleles() -> Result<(), VCFError> {
+    let record = VCFRecord::from_bytes(
+        b"1\t100\trs1\tA\tC,G\t30\tPASS\tDB;AC=1,2;AF=0.25,0.5;RC=10,5,2;DP=17;XX=1,2\tGT:AD:PL:VAF:FT\t0/1:10,5,0:0,10,20,30,40,50:0.3,0:PASS\t1|2:.:.:.\t2:1,2,3:0,1,2",
+        1,
+        create_typed_header(),
+    )?;
+    let split = record.split_alleles()?;
+    assert_eq!(split.len(), 2);
+    let lines: Vec<U8Vec> = split
+        .iter()
+        .map(|x| {
+            let mut line = Vec::new();
+            x.write_record(&mut line).unwrap();
+            line
+        })
+        .collect();
+    assert_eq!(
+        lines[0],
+        b"1\t100\trs1\tA\tC\t30.0\tPASS\tDB;AC=1;AF=0.25;RC=10,5;DP=17;XX=1,2\tGT:AD:PL:VAF:FT\t0/1:10,5:0,10,20:0.3:PASS\t1|0:.:.:.\t0:1,2:0,1\n".to_vec()
+    );
+    assert_eq!(
+        lines[1],
+        b"1\t100\trs1\tA\tG\t30.0\tPASS\tDB;AC=2;AF=0.5;RC=10,2;DP=17;XX=1,2\tGT:AD:PL:VAF:FT\t0/0:10,0:0,30,50:0:PASS\t0|1:.:.:.\t1:1,3:0,2\n".to_vec()
+    );
+    assert_eq!(
+        split[1].info_value(b"AC")?,
+        Some(InfoValue::Integer(vec![Some(2)]))
+    );
+    assert_eq!(
+        split[1].genotype_call(b"S2")?,
+        Some(Genotype::from_bytes(b"0|1")?)
+    );
+
+    let record = VCFRecord::from_bytes(
+        b"1\t100\t.\tA\tC,G,T\t.\t.\t.\tGT:PL\t0/0/3:0,1,2,3,4,5,6,7,8,9,10,11,12,13,14,15,16,17,18,19",
+        1,
+        create_typed_header(),
+    )?;
+    let split = record.split_alleles()?;
+    assert_eq!(split.len(), 3);
+    assert_eq!(
+        split[2].genotype(b"S1", b"GT"),
+        Some(&vec![b"0/0/1".to_vec()])
+    );
+    assert_eq!(
+        split[2].genotype_value(b"S1", b"PL")?,
+        Some(FormatValue::Integer(vec![
+            Some(0),
+            Some(10),
+            Some(16),
+            Some(19)
+        ]))
+    );
+    assert_eq!(
+        split[0].genotype_value(b"S1", b"PL")?,
+        Some(FormatValue::Integer(vec![
+            Some(0),
+            Some(1),
+            Some(2),
+            Some(3)
+        ]))
+    );
+
+    let record = VCFRecord::from_bytes(b"1\t100\t.\tA\tC\t.\t.\tAC=1", 1, create_typed_header())?;
+    assert_eq!(record.split_alleles()?.len(), 1);
+
+    let record = VCFRecord::from_bytes(b"1\t100\t.\tA\tC,G\t.\t.\tAC=1", 1, create_typed_header())?;
+    assert!(record.split_alleles().is_err());
+    Ok(())
+}
+
+#[test]
+fn test_join_alleles() -> Result<(), VCFError> {
+    let record = VCFRecord::from_bytes(
+        b"1\t100\trs1\tA\tC,G\t30\tPASS\tDB;AC=1,2;AF=0.25,0.5;RC=10,5,2;DP=17;XX=1,2\tGT:AD:PL:VAF:FT\t0/1:10,5,0:0,10,20,30,40,50:0.3,0:PASS\t1|2:.:.:.\t2:1,2,3:0,1,2",
+        1,
+        create_typed_header(),
+    )?;
+    let mut records = record.split_alleles()?;
+    records.push(VCFRecord::from_bytes(
+        b"1\t100\trs2\tAT\tA\t10\tq10\tAC=3\tGT:FT\t1/1:PASS\t0/1:PASS\t./.:.",
+        1,
+        create_typed_header(),
+    )?);
+    records.push(VCFRecord::from_bytes(
+        b"1\t100\trs3\tAT\tATT\t20\tPASS\tAC=1;DP=5\tGT:FT\t0/1:PASS\t0/0:PASS\t1/1:.",
+        1,
+        create_typed_header(),
+    )?);
+    let joined = VCFRecord::join_alleles(&records)?;
+    assert_eq!(joined.len(), 2);
+    let lines: Vec<U8Vec> = joined
+        .iter()
+        .map(|x| {
+            let mut line = Vec::new();
+            x.write_record(&mut line).unwrap();
+            line
+        })
+        .collect();
+    assert_eq!(
+        lines[0],
+        b"1\t100\trs1\tA\tC,G\t30.0\tPASS\tDB;AC=1,2;AF=0.25,0.5;RC=10,5,2;DP=17;XX=1,2\tGT:AD:PL:VAF:FT\t0/1:10,5,0:0,10,20,30,.,50:0.3,0:PASS\t1|2:.,.,.:.,.,.,.,.,.:.,.:.\t2:1,2,3:0,1,2:.,.:.\n".to_vec()
+    );
+    assert_eq!(
+        lines[1],
+        b"1\t100\trs2,rs3\tAT\tA,ATT\t20.0\tq10\tAC=3,1;DP=5\tGT:FT\t1/1:PASS\t0/1:PASS\t2/2:.\n"
+            .to_vec()
+    );
+    assert_eq!(
+        joined[1].info_value(b"AC")?,
+        Some(InfoValue::Integer(vec![Some(3), Some(1)]))
+    );
+
+    let records = vec![
+        VCFRecord::from_bytes(
+            b"1\t100\t.\tA\tC\t.\t.\t.\tGT:PL\t0/1:1,2,3\t0|1:.",
+            1,
+            create_typed_header(),
+        )?,
+        VCFRecord::from_bytes(
+            b"1\t100\t.\tA\tG\t.\t.\t.\tGT:PL\t0/1:4,5,6\t1|0:.",
+            1,
+            create_typed_header(),
+        )?,
+    ];
+    let joined = VCFRecord::join_alleles(&records)?;
+    assert_eq!(joined.len(), 1);
+    assert_eq!(joined[0].genotype_call(b"S1")?, Some("1/2".parse()?));
+    assert_eq!(joined[0].genotype_call(b"S2")?, Some("2|1".parse()?));
+    assert_eq!(
+        joined[0].genotype(b"S1", b"PL"),
+        Some(&vec![
+            b"1".to_vec(),
+            b"2".to_vec(),
+            b"3".to_vec(),
+            b"5".to_vec(),
+            b".".to_vec(),
+            b"6".to_vec()
+        ])
+    );
+    assert_eq!(joined[0].split_alleles()?.len(), 2);
+
+    let records = vec![
+        VCFRecord::from_bytes(b"1\t100\t.\tA\tC\t.\t.\tAC=1", 1, create_typed_header())?,
+        VCFRecord::from_bytes(b"1\t100\t.\tA\tG\t.\t.\tAC=1,2", 1, create_typed_header())?,
+    ];
+    assert!(VCFRecord::join_alleles(&records).is_err());
+    assert!(VCFRecord::join_alleles(&[])?.is_empty());
+    Ok(())
+}
+
+#[test]
+fn test_record_view() -> Result<(), VCFError> {
+    let header = create_typed_header();
+    let view = VCFRecordView::new(
+        b"1\t100\trs1,rs2\tA\tC,G\t30.5\tq10\tDB;AC=1,.;DP=.\tGT:AD\t0/1:10,5,0\t1|2\t.\n",
+        1,
+        &header,
+    )?;
+    assert_eq!(view.chromosome(), b"1");
+    assert_eq!(view.position(), 100);
+    assert_eq!(view.id().collect::<Vec<_>>(), vec![b"rs1", b"rs2"]);
+    assert_eq!(view.reference(), b"A");
+    assert_eq!(view.alternative().collect::<Vec<_>>(), vec![b"C", b"G"]);
+    assert_eq!(view.qual(), Some(30.5));
+    assert_eq!(view.filter().collect::<Vec<_>>(), vec![b"q10"]);
+    assert_eq!(view.info(b"DB").unwrap().count(), 0);
+    assert_eq!(
+        view.info(b"AC").unwrap().collect::<Vec<_>>(),
+        vec![&b"1"[..], b"."]
+    );
+    assert_eq!(view.info(b"DP").unwrap().collect::<Vec<_>>(), vec![b"."]);
+    assert!(view.info(b"AF").is_none());
+    assert_eq!(view.format().collect::<Vec<_>>(), vec![&b"GT"[..], b"AD"]);
+    assert_eq!(view.sample(1), Some(&b"1|2"[..]));
+    assert_eq!(view.sample(3), None);
+    assert_eq!(
+        view.genotype(b"S1", b"AD").unwrap().collect::<Vec<_>>(),
+        vec![&b"10"[..], b"5", b"0"]
+    );
+    assert!(view.genotype(b"S2", b"AD").is_none());
+    assert_eq!(
+        view.genotype(b"S3", b"GT").unwrap().collect::<Vec<_>>(),
+        vec![b"."]
+    );
+    assert!(view.genotype(b"S4", b"GT").is_none());
+
+    let view = VCFRecordView::new(b"1\t100\t.\tA\t.", 1, &header)?;
+    assert_eq!(view.id().count(), 0);
+    assert_eq!(view.alternative().count(), 0);
+    assert_eq!(view.qual(), None);
+    assert_eq!(view.filter().count(), 0);
+    assert!(view.info(b"DP").is_none());
+    assert_eq!(view.format().count(), 0);
+    assert_eq!(view.sample(0), None);
+
+    for one in [
+        &b"1\t100\t.\tA"[..],
+        b"1\tX\t.\tA\tC",
+        b"1\t100\t.\t\tC",
+        b"1\t100\t.\tA\tC\tX",
+    ]
+    .iter()
+    {
+        assert!(matches!(
+            VCFRecordView::new(one, 3, &header),
+            Err(VCFError::RecordParseError(x)) if x.line == Some(3)
+        ));
+    }
+
+    let data = include_bytes!("../../testfiles/1kGP-subset.vcf");
+    let mut reader = io::BufReader::new(&data[..]);
+    let (_, header) = crate::header::parse_header(&mut reader).unwrap();
+    let mut count = 0;
+    for line in io::BufRead::split(reader, b'\n') {
+        let line = line?;
+        let view = VCFRecordView::new(&line, 0, &header)?;
+        let record = view.to_record()?;
+        assert_eq!(view.chromosome(), &record.chromosome[..]);
+        assert_eq!(view.position(), record.position);
+        assert_eq!(view.id().collect::<Vec<_>>(), record.id);
+        assert_eq!(view.reference(), &record.reference[..]);
+        assert_eq!(view.alternative().collect::<Vec<_>>(), record.alternative);
+        assert_eq!(view.qual(), record.qual);
+        assert_eq!(view.filter().collect::<Vec<_>>(), record.filter);
+        for (key, values) in record.info.iter() {
+            assert_eq!(&view.info(key).unwrap().collect::<Vec<_>>(), values);
+        }
+        assert_eq!(view.format().collect::<Vec<_>>(), record.format);
+        for sample in header.samples() {
+            for key in record.format.iter() {
+                assert_eq!(
+                    view.genotype(sample, key)
+                        .map(|x| x.map(|y| y.to_vec()).collect()),
+                    record.genotype(sample, key).cloned()
+                );
+            }
+        }
+        count += 1;
+    }
+    assert_eq!(count, 306);
+    Ok(())
+}
+
+#[test]
+fn test_record_parse_error() {
+    let header = create_typed_header();
+    for (line, column, field, text, reason) in [
+        (
+            &b"1\tX\t.\tA\tC"[..],
+            3,
+            &b"POS"[..],
+            &b"X"[..],
+            "Position is not an integer",
+        ),
+        (
+            b"1\t12a\t.\tA\tC\n",
+            5,
+            b"POS",
+            b"12a",
+            "Position is not an integer",
+        ),
+        (b"1\t100\t.\tA", 10, b"ALT", b"", "Missing column"),
+        (b"1\t100\t.\t\tC", 9, b"REF", b"", "Empty column"),
+        (
+            b"1\t100\t.\tA\tC\t3x\t.",
+            14,
+            b"QUAL",
+            b"3x",
+            "Quality is not a number",
+        ),
+        (
+            b"1\t100\t.\tA\tC\tx3\t.",
+            13,
+            b"QUAL",
+            b"x3",
+            "Quality is not a number",
+        ),
+        (
+            b"1\t100\t.\tA\tC\t.\t.\tDP=1;=5",
+            22,
+            b"INFO",
+            b"=5",
+            "INFO entry has no key",
+        ),
+    ]
+    .iter()
+    {
+        let expected = ParseErrorDetail::new(7, *column, field, text, reason);
+        match VCFRecord::from_bytes(line, 7, header.clone()) {
+            Err(VCFError::RecordParseError(x)) => assert_eq!(x, expected),
+            _ => panic!("{}", String::from_utf8_lossy(line)),
+        }
+    }
+
+    for line in [&b"1\tX\t.\tA\tC"[..], b"1\t100\t.\tA", b"1\t100\t.\t\tC"].iter() {
+        let record_error = VCFRecord::from_bytes(line, 7, header.clone()).unwrap_err();
+        let view_error = VCFRecordView::new(line, 7, &header).unwrap_err();
+        match (record_error, view_error) {
+            (VCFError::RecordParseError(x), VCFError::RecordParseError(y)) => assert_eq!(x, y),
+            _ => panic!("{}", String::from_utf8_lossy(line)),
+        }
+    }
+
+    match super::parser::record_error(b"1\t100\t.\tA\tC", 7, 10, Some("Bad allele"), &header) {
+        VCFError::RecordParseError(x) => {
+            assert_eq!(x, ParseErrorDetail::new(7, 11, b"ALT", b"C", "Bad allele"))
+        }
+        x => panic!("{}", x),
+    }
+
+    let error = VCFRecord::from_bytes(b"1\tX\t.\tA\tC", 7, header).unwrap_err();
+    assert_eq!(
+        error.to_string(),
+        "Failed to parse record at line: 7, column: 3, POS: Position is not an integer (\"X\")"
+    );
+}
+
+#[test]
+fn test_validate() -> Result<(), VCFError> {
+    let vcf_data = &b"##fileformat=VCFv4.2
+##contig=<ID=1,length=1000>
+##contig=<ID=2>
+##FILTER=<ID=q10,Description=\"Quality below 10\">
+##INFO=<ID=DB,Number=0,Type=Flag,Description=\"dbSNP membership\">
+##INFO=<ID=AC,Number=A,Type=Integer,Description=\"Allele count\">
+##FORMAT=<ID=GT,Number=1,Type=String,Description=\"Genotype\">
+##FORMAT=<ID=PL,Number=G,Type=Integer,Description=\"Genotype likelihoods\">
+#CHROM\tPOS\tID\tREF\tALT\tQUAL\tFILTER\tINFO\tFORMAT\tS1\tS2
+"[..];
+    let (_, header) = crate::header::parse_header(&mut &vcf_data[..])?;
+
+    let record = VCFRecord::from_bytes(
+        b"1\t100\t.\tA\tC,G\t.\tPASS\tDB;AC=1,2\tGT:PL\t0/1:0,1,2,3,4,5\t1:0,1,2",
+        1,
+        header.clone(),
+    )?;
+    assert_eq!(validate(&record, &header), vec![]);
+    assert_eq!(record.validate(), vec![]);
+
+    let record = VCFRecord::from_bytes(
+        b"1\t1001\t.\tA\tC\t.\tLowQual\tDB=1;AC=1,2;XX=1\tGT:PL:AD\t0/1:0,1,x\t0:0,1,2:3",
+        1,
+        header.clone(),
+    )?;
+    assert_eq!(
+        validate(&record, &header),
+        vec![
+            Violation::PositionOutOfContig {
+                contig: b"1".to_vec(),
+                position: 1001,
+                length: 1000
+            },
+            Violation::UndeclaredFilter(b"LowQual".to_vec()),
+            Violation::WrongType {
+                field: b"INFO/DB".to_vec(),
+                sample: None,
+                reason: "Flag has value"
+            },
+            Violation::WrongNumber {
+                field: b"INFO/AC".to_vec(),
+                sample: None
+            },
+            Violation::UndeclaredInfo(b"XX".to_vec()),
+            Violation::UndeclaredFormat(b"AD".to_vec()),
+            Violation::WrongType {
+                field: b"FORMAT/PL".to_vec(),
+                sample: Some(b"S1".to_vec()),
+                reason: "Not an integer"
+            },
+            Violation::WrongNumber {
+                field: b"FORMAT/PL".to_vec(),
+                sample: Some(b"S2".to_vec()),
+            },
+        ]
+    );
+
+    let record = VCFRecord::from_bytes(b"3\t1\t.\tA\tC", 1, header.clone())?;
+    assert_eq!(
+        validate(&record, &header),
+        vec![Violation::UnknownContig(b"3".to_vec())]
+    );
+    assert_eq!(
+        validate(&record, &header)[0].to_string(),
+        "Contig is not declared in header: 3"
+    );
+
+    let violation = Violation::WrongType {
+        field: b"FORMAT/PL".to_vec(),
+        sample: Some(b"S1".to_vec()),
+        reason: "Not an integer",
+    };
+    assert_eq!(
+        violation.to_string(),
+        "Not an integer: FORMAT/PL (sample: S1)"
+    );
+    let violation = Violation::WrongNumber {
+        field: b"FORMAT/PL".to_vec(),
+        sample: Some(b"S2".to_vec()),
+    };
+    assert_eq!(
+        violation.to_string(),
+        "Wrong number of values: FORMAT/PL (sample: S2)"
+    );
+    let violation = Violation::WrongNumber {
+        field: b"INFO/AC".to_vec(),
+        sample: None,
+    };
+    assert_eq!(violation.to_string(), "Wrong number of values: INFO/AC");
+    Ok(())
+}
@@ -0,0 +1,165 @@
+use super::value::{parse_values, WRONG_NUMBER};
+use super::VCFRecord;
+use crate::{U8Vec, VCFError, VCFHeader};
+use thiserror::Error;
+
+/// A problem of a record found by `validate`.
+/// `field` is `INFO/<key>` or `FORMAT/<key>`, and `sample` is the sample name for FORMAT.
+#[derive(Error, Debug, Clone, PartialEq, Eq, Hash)]
+pub enum Violation {
+    #[error("INFO is not declared in header: {}", String::from_utf8_lossy(_0))]
+    UndeclaredInfo(U8Vec),
+    #[error("FORMAT is not declared in header: {}", String::from_utf8_lossy(_0))]
+    UndeclaredFormat(U8Vec),
+    #[error("FILTER is not declared in header: {}", String::from_utf8_lossy(_0))]
+    UndeclaredFilter(U8Vec),
+    #[error("Contig is not declared in header: {}", String::from_utf8_lossy(_0))]
+    UnknownContig(U8Vec),
+    #[error(
+        "Position is past the end of contig: {}:{} (length: {})",
+        String::from_utf8_lossy(contig),
+        position,
+        length
+    )]
+    PositionOutOfContig {
+        contig: U8Vec,
+        position: u64,
+        length: u64,
+    },
+    #[error(
+        "Wrong number of values: {}{}",
+        String::from_utf8_lossy(field),
+        in_sample(sample)
+    )]
+    WrongNumber { field: U8Vec, sample: Option<U8Vec> },
+    #[error("{}: {}{}", reason, String::from_utf8_lossy(field), in_sample(sample))]
+    WrongType {
+        field: U8Vec,
+        sample: Option<U8Vec>,
+        reason: &'static str,
+    },
+}
+
+fn in_sample(sample: &Option<U8Vec>) -> String {
+    sample
+        .as_ref()
+        .map(|x| format!(" (sample: {})", String::from_utf8_lossy(x)))
+        .unwrap_or_default()
+}
+
+/// Check that the contig, FILTER ids, INFO and FORMAT keys of `record` are declared
+/// in `header`, and that the values match `Number` and `Type` of the declarations.
+/// Return an empty list if no problem was found.
+pub fn validate(record: &VCFRecord, header: &VCFHeader) -> Vec<Violation> {
+    let mut violations = Vec::new();
+
+    match header.contig(&record.chromosome) {
+        Some(contig) => {
+            if let Some(length) = contig.length.filter(|x| record.position > *x) {
+                violations.push(Violation::PositionOutOfContig {
+                    contig: record.chromosome.clone(),
+                    position: record.position,
+                    length,
+                });
+            }
+        }
+        None => violations.push(Violation::UnknownContig(record.chromosome.clone())),
+    }
+
+    for one in record.filter.iter() {
+        if one != b"PASS" && header.filter(one).is_none() {
+            violations.push(Violation::UndeclaredFilter(one.clone()));
+        }
+    }
+
+    let allele_count = record.alternative.len() + 1;
+    for (key, values) in record.info.iter() {
+        match header.info(key) {
+            Some(definition) => {
+                let result = parse_values(
+                    key,
+                    values,
+                    definition.value_type,
+                    definition.number,
+                    allele_count,
+                    2,
+                );
+                check_value(&mut violations, b"INFO/", key, None, result.err());
+            }
+            None => violations.push(Violation::UndeclaredInfo(key.clone())),
+        }
+    }
+
+    for key in record.format.iter() {
+        if header.format(key).is_none() {
+            violations.push(Violation::UndeclaredFormat(key.clone()));
+        }
+    }
+    for sample_index in 0..record.sample_genotype_count() {
+        let sample = match record.sample_genotype(sample_index) {
+            Some(x) => x,
+            None => continue,
+        };
+        let ploidy = record
+            .format
+            .iter()
+            .position(|x| x == b"GT")
+            .and_then(|x| sample.get(x))
+            .and_then(|x| x.first())
+            .map(|x| x.split(|y| *y == b'/' || *y == b'|').count())
+            .unwrap_or(2);
+        // Omitted trailing fields are missing values
+        for (key, values) in record.format.iter().zip(sample.iter()) {
+            let definition = match header.format(key) {
+                Some(x) => x,
+                None => continue,
+            };
+            if values.is_empty() {
+                continue;
+            }
+            let result = parse_values(
+                key,
+                values,
+                definition.value_type,
+                definition.number,
+                allele_count,
+                ploidy,
+            );
+            let sample_name = header.samples().get(sample_index).cloned();
+            check_value(&mut violations, b"FORMAT/", key, sample_name, result.err());
+        }
+    }
+
+    violations
+}
+
+fn check_value(
+    violations: &mut Vec<Violation>,
+    prefix: &[u8],
+    key: &[u8],
+    sample: Option<U8Vec>,
+    error: Option<VCFError>,
+) {
+    let reason = match error {
+        Some(VCFError::TypedValueError(_, x)) => x,
+        _ => return,
+    };
+    let mut field = prefix.to_vec();
+    field.extend_from_slice(key);
+    violations.push(if reason == WRONG_NUMBER {
+        Violation::WrongNumber { field, sample }
+    } else {
+        Violation::WrongType {
+            field,
+            sample,
+            reason,
+        }
+    });
+}
+
+impl VCFRecord {
+    /// Check this record against its header. See `validate`.
+    pub fn validate(&self) -> Vec<Violation> {
+        validate(self, &self.header)
+    }
+}
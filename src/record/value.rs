@@ -0,0 +1,143 @@
+use crate::{Number, U8Vec, VCFError, ValueType};
+use std::str;
+
+/// Typed value of INFO field. Missing values (`.`) are `None`.
+#[derive(Debug, Clone, PartialEq)]
+pub enum InfoValue {
+    Integer(Vec<Option<i32>>),
+    Float(Vec<Option<f64>>),
+    Flag,
+    Character(Vec<Option<u8>>),
+    String(Vec<Option<U8Vec>>),
+}
+
+/// Typed value of FORMAT field of one sample.
+pub type FormatValue = InfoValue;
+
+impl InfoValue {
+    pub fn integers(&self) -> Option<&[Option<i32>]> {
+        match self {
+            InfoValue::Integer(x) => Some(x),
+            _ => None,
+        }
+    }
+
+    pub fn floats(&self) -> Option<&[Option<f64>]> {
+        match self {
+            InfoValue::Float(x) => Some(x),
+            _ => None,
+        }
+    }
+
+    pub fn characters(&self) -> Option<&[Option<u8>]> {
+        match self {
+            InfoValue::Character(x) => Some(x),
+            _ => None,
+        }
+    }
+
+    pub fn strings(&self) -> Option<&[Option<U8Vec>]> {
+        match self {
+            InfoValue::String(x) => Some(x),
+            _ => None,
+        }
+    }
+}
+
+/// Reason of `TypedValueError` when the number of values does not match `Number`.
+pub(crate) const WRONG_NUMBER: &str = "Wrong number of values";
+
+/// Parse values according to `value_type` and check the number of values.
+/// `allele_count` is the number of alleles including the reference.
+/// `ploidy` is used to count genotypes for `Number=G`.
+pub(crate) fn parse_values(
+    key: &[u8],
+    values: &[U8Vec],
+    value_type: &ValueType,
+    number: &Number,
+    allele_count: usize,
+    ploidy: usize,
+) -> Result<InfoValue, VCFError> {
+    let error = |message| VCFError::TypedValueError(key.to_vec(), message);
+
+    if let ValueType::Flag = value_type {
+        if !values.is_empty() {
+            return Err(error("Flag has value"));
+        }
+        return Ok(InfoValue::Flag);
+    }
+
+    // A single "." means that all values are missing
+    if values.len() != 1 || values[0] != b"." {
+        let expected = match number {
+            Number::Number(x) => Some(*x as usize),
+            Number::Allele => Some(allele_count.saturating_sub(1)),
+            Number::Reference => Some(allele_count),
+            Number::Genotype => genotype_count(allele_count, ploidy),
+            Number::Zero => Some(0),
+            Number::Unknown | Number::Other(_) => None,
+        };
+        if expected.map(|x| x != values.len()).unwrap_or(false) {
+            return Err(error(WRONG_NUMBER));
+        }
+    }
+
+    let is_missing = |x: &U8Vec| x == b".";
+    Ok(match value_type {
+        ValueType::Integer => InfoValue::Integer(
+            values
+                .iter()
+                .map(|x| {
+                    if is_missing(x) {
+                        return Ok(None);
+                    }
+                    str::from_utf8(x)
+                        .ok()
+                        .and_then(|y| y.parse().ok())
+                        .map(Some)
+                        .ok_or_else(|| error("Not an integer"))
+                })
+                .collect::<Result<_, _>>()?,
+        ),
+        ValueType::Float => InfoValue::Float(
+            values
+                .iter()
+                .map(|x| {
+                    if is_missing(x) {
+                        return Ok(None);
+                    }
+                    str::from_utf8(x)
+                        .ok()
+                        .and_then(|y| y.parse().ok())
+                        .map(Some)
+                        .ok_or_else(|| error("Not a float"))
+                })
+                .collect::<Result<_, _>>()?,
+        ),
+        ValueType::Character => InfoValue::Character(
+            values
+                .iter()
+                .map(|x| match &x[..] {
+                    b"." => Ok(None),
+                    [y] => Ok(Some(*y)),
+                    _ => Err(error("Not a character")),
+                })
+                .collect::<Result<_, _>>()?,
+        ),
+        ValueType::String | ValueType::Other(_) => InfoValue::String(
+            values
+                .iter()
+                .map(|x| if is_missing(x) { None } else { Some(x.clone()) })
+                .collect(),
+        ),
+        ValueType::Flag => unreachable!(),
+    })
+}
+
+/// Number of unordered genotypes, which is the number of combinations
+/// with repetition of `ploidy` alleles. Return `None` on overflow.
+pub(crate) fn genotype_count(allele_count: usize, ploidy: usize) -> Option<usize> {
+    (1..=ploidy).try_fold(1usize, |count, i| {
+        Some(count.checked_mul(allele_count.checked_add(i - 1)?)? / i)
+    })
+}
@@ -0,0 +1,177 @@
+use super::VCFRecord;
+use crate::{VCFError, VCFHeader};
+use once_cell::unsync::OnceCell;
+use std::str;
+
+/// Record which borrows from a line without copying.
+/// Columns are split on construction, and samples are located on first access.
+#[derive(Debug, Clone)]
+pub struct VCFRecordView<'a> {
+    line: &'a [u8],
+    line_num: u64,
+    header: &'a VCFHeader,
+    columns: [&'a [u8]; 9],
+    samples: &'a [u8],
+    sample_columns: OnceCell<Vec<&'a [u8]>>,
+    sample_subset: Option<&'a [usize]>,
+    position: u64,
+    qual: Option<f64>,
+}
+
+impl<'a> VCFRecordView<'a> {
+    pub fn new(line: &'a [u8], line_num: u64, header: &'a VCFHeader) -> Result<Self, VCFError> {
+        let line = line.strip_suffix(b"\n").unwrap_or(line);
+        let line = line.strip_suffix(b"\r").unwrap_or(line);
+        let error = |offset| super::parser::record_error(line, line_num, offset, None, header);
+
+        // Missing optional columns are same as "."
+        let mut split = line.splitn(10, |x| *x == b'\t');
+        let mut columns = [&b"."[..]; 9];
+        let mut column_count = 0;
+        for (one, column) in columns.iter_mut().zip(&mut split) {
+            *one = column;
+            column_count += 1;
+        }
+        let samples = split.next().unwrap_or(&[]);
+        if column_count < 5 {
+            return Err(error(line.len()));
+        }
+        // Byte offset where a column starts
+        let offset = |i: usize| columns[..i].iter().map(|x| x.len() + 1).sum::<usize>();
+        if let Some(i) = columns.iter().take(5).position(|x| x.is_empty()) {
+            return Err(error(offset(i)));
+        }
+
+        let position = Some(columns[1])
+            .filter(|x| x.iter().all(|y| y.is_ascii_digit()))
+            .and_then(|x| str::from_utf8(x).ok())
+            .and_then(|x| x.parse().ok())
+            .ok_or_else(|| error(offset(1)))?;
+        let qual = match columns[5] {
+            b"." => None,
+            x => Some(
+                str::from_utf8(x)
+                    .ok()
+                    .and_then(|y| y.parse().ok())
+                    .ok_or_else(|| error(offset(5)))?,
+            ),
+        };
+
+        Ok(VCFRecordView {
+            line,
+            line_num,
+            header,
+            columns,
+            samples,
+            sample_columns: OnceCell::new(),
+            sample_subset: None,
+            position,
+            qual,
+        })
+    }
+
+    /// Use only sample columns at 0-based indexes in the line.
+    pub(crate) fn with_sample_subset(mut self, sample_subset: Option<&'a [usize]>) -> Self {
+        self.sample_subset = sample_subset;
+        self
+    }
+
+    pub fn header(&self) -> &'a VCFHeader {
+        self.header
+    }
+
+    pub fn chromosome(&self) -> &'a [u8] {
+        self.columns[0]
+    }
+
+    pub fn position(&self) -> u64 {
+        self.position
+    }
+
+    pub fn id(&self) -> impl Iterator<Item = &'a [u8]> {
+        split_column(self.columns[2], b',')
+    }
+
+    pub fn reference(&self) -> &'a [u8] {
+        self.columns[3]
+    }
+
+    pub fn alternative(&self) -> impl Iterator<Item = &'a [u8]> {
+        split_column(self.columns[4], b',')
+    }
+
+    pub fn qual(&self) -> Option<f64> {
+        self.qual
+    }
+
+    pub fn filter(&self) -> impl Iterator<Item = &'a [u8]> {
+        split_column(self.columns[6], b',')
+    }
+
+    /// Values of the INFO key. Flag has no values.
+    pub fn info(&self, key: &[u8]) -> Option<impl Iterator<Item = &'a [u8]>> {
+        split_column(self.columns[7], b';')
+            .map(|x| match x.iter().position(|y| *y == b'=') {
+                Some(y) => (&x[..y], &x[y + 1..]),
+                None => (x, &b""[..]),
+            })
+            .find(|(x, _)| *x == key)
+            .map(|(_, x)| split_values(x, b','))
+    }
+
+    pub fn format(&self) -> impl Iterator<Item = &'a [u8]> {
+        split_column(self.columns[8], b':')
+    }
+
+    /// Whole column of the sample.
+    pub fn sample(&self, sample_index: usize) -> Option<&'a [u8]> {
+        let sample_index = match self.sample_subset {
+            Some(x) => *x.get(sample_index)?,
+            None => sample_index,
+        };
+        let samples = self.samples;
+        self.sample_columns
+            .get_or_init(|| {
+                if samples.is_empty() {
+                    Vec::new()
+                } else {
+                    samples.split(|x| *x == b'\t').collect()
+                }
+            })
+            .get(sample_index)
+            .copied()
+    }
+
+    /// Values of the FORMAT key of the sample.
+    pub fn genotype(
+        &self,
+        sample_name: &[u8],
+        key: &[u8],
+    ) -> Option<impl Iterator<Item = &'a [u8]>> {
+        let sample = self.sample(self.header.sample_index(sample_name)?)?;
+        let index = self.format().position(|x| x == key)?;
+        sample
+            .split(|x| *x == b':')
+            .nth(index)
+            .map(|x| split_values(x, b','))
+    }
+
+    /// Parse whole line into an owned record.
+    pub fn to_record(&self) -> Result<VCFRecord, VCFError> {
+        let mut record = VCFRecord::new(self.header.clone());
+        record.parse_bytes_with(self.line, self.line_num, self.sample_subset, false)?;
+        Ok(record)
+    }
+}
+
+/// Split column. "." is an empty column.
+fn split_column(column: &[u8], separator: u8) -> impl Iterator<Item = &[u8]> {
+    let column = if column == b"." { &b""[..] } else { column };
+    split_values(column, separator)
+}
+
+/// Split values. Empty data has no values.
+fn split_values(data: &[u8], separator: u8) -> impl Iterator<Item = &[u8]> {
+    data.split(move |x| *x == separator)
+        .take(if data.is_empty() { 0 } else { usize::MAX })
+}
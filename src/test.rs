@@ -2,6 +2,15 @@ use super::*;
 
 use std::io::BufReader;
 
+/// Concatenate records written without a header.
+pub(crate) fn write_records(records: &[VCFRecord]) -> Vec<u8> {
+    let mut data = Vec::new();
+    for one in records {
+        one.write_record(&mut data).unwrap();
+    }
+    data
+}
+
 #[test]
 fn test_reader1() -> Result<(), VCFError> {
     let mut simple_vcf = BufReader::new(&include_bytes!("../testfiles/simple1.vcf")[..]);
@@ -41,6 +50,237 @@ fn test_reader3() -> Result<(), VCFError> {
     Ok(())
 }
 
+#[test]
+fn test_records() -> Result<(), VCFError> {
+    let data = &include_bytes!("../testfiles/1kGP-subset.vcf")[..];
+    let mut vcf_reader = VCFReader::new(BufReader::new(data))?;
+    let records = vcf_reader.records().collect::<Result<Vec<_>, _>>()?;
+    assert_eq!(records.len(), 306);
+    assert!(vcf_reader.records().next().is_none());
+
+    let mut vcf_reader = VCFReader::new(BufReader::new(data))?;
+    let mut positions = Vec::new();
+    vcf_reader.for_each_record(|record| -> Result<bool, VCFError> {
+        positions.push(record.position);
+        Ok(positions.len() < 10)
+    })?;
+    assert_eq!(positions.len(), 10);
+    assert_eq!(
+        positions,
+        records[..10].iter().map(|x| x.position).collect::<Vec<_>>()
+    );
+
+    let mut vcf_reader = VCFReader::new(BufReader::new(data))?;
+    let mut other_reader = VCFReader::new(BufReader::new(data))?;
+    let snv_count = vcf_reader
+        .records()
+        .zip(other_reader.records().skip(1))
+        .filter_map(|(x, y)| Some((x.ok()?, y.ok()?)))
+        .take_while(|(x, y)| x.position <= y.position)
+        .filter(|(x, _)| x.reference.len() == 1 && x.alternative.iter().all(|y| y.len() == 1))
+        .count();
+    assert_eq!(
+        snv_count,
+        records
+            .iter()
+            .take(305)
+            .filter(|x| x.reference.len() == 1 && x.alternative.iter().all(|y| y.len() == 1))
+            .count()
+    );
+
+    let mut vcf_reader = VCFReader::new(BufReader::new(
+        &b"##fileformat=VCFv4.2\n#CHROM\tPOS\tID\tREF\tALT\tQUAL\tFILTER\tINFO\n1\t1\t.\tA\tC\t.\t.\t.\n1\tX\n1\t3\t.\tA\tC\t.\t.\t.\n"[..],
+    ))?;
+    let mut records = vcf_reader.records();
+    assert!(records.next().unwrap().is_ok());
+    assert!(matches!(
+        records.next(),
+        Some(Err(VCFError::RecordParseError(x))) if x.line == Some(4)
+    ));
+    assert!(records.next().is_none());
+
+    let mut vcf_reader = VCFReader::new(BufReader::new(data))?;
+    let result = vcf_reader.for_each_record(|_| -> Result<bool, VCFError> {
+        Err(VCFError::UnknownSample(b"x".to_vec()))
+    });
+    assert!(result.is_err());
+    Ok(())
+}
+
+#[test]
+fn test_record_view() -> Result<(), VCFError> {
+    let data = &include_bytes!("../testfiles/1kGP-subset.vcf")[..];
+    let mut vcf_reader = VCFReader::new(BufReader::new(data))?;
+    let records = vcf_reader.records().collect::<Result<Vec<_>, _>>()?;
+
+    let mut vcf_reader = VCFReader::new(BufReader::new(data))?;
+    let mut count = 0;
+    while let Some(view) = vcf_reader.next_record_view()? {
+        let mut expected = Vec::new();
+        records[count].write_record(&mut expected)?;
+        let mut written = Vec::new();
+        view.to_record()?.write_record(&mut written)?;
+        assert_eq!(written, expected);
+        count += 1;
+    }
+    assert_eq!(count, records.len());
+    Ok(())
+}
+
+#[test]
+fn test_lazy_genotype() -> Result<(), VCFError> {
+    let data = &include_bytes!("../testfiles/1kGP-subset.vcf")[..];
+    let mut vcf_reader = VCFReader::new(BufReader::new(data))?;
+    let records = vcf_reader.records().collect::<Result<Vec<_>, _>>()?;
+
+    let mut vcf_reader = VCFReader::new(BufReader::new(data))?;
+    vcf_reader.set_lazy_genotype(true);
+    let lazy_records = vcf_reader.records().collect::<Result<Vec<_>, _>>()?;
+    assert_eq!(lazy_records.len(), records.len());
+
+    let mut bcf_data = Vec::new();
+    let mut lazy_bcf_data = Vec::new();
+    let mut bcf_writer = BCFWriter::new(&mut bcf_data, vcf_reader.header())?;
+    let mut lazy_bcf_writer = BCFWriter::new(&mut lazy_bcf_data, vcf_reader.header())?;
+    for (record, lazy_record) in records.iter().zip(lazy_records.iter()) {
+        assert!(lazy_record.is_genotype_lazy());
+        assert!(lazy_record.genotype.is_empty());
+        assert_eq!(lazy_record.info, record.info);
+
+        let mut expected = Vec::new();
+        record.write_record(&mut expected)?;
+        let mut written = Vec::new();
+        lazy_record.write_record(&mut written)?;
+        assert_eq!(written, expected);
+
+        for sample in vcf_reader.header().samples() {
+            for key in record.format.iter() {
+                assert_eq!(
+                    lazy_record.genotype(sample, key),
+                    record.genotype(sample, key)
+                );
+            }
+        }
+        assert_eq!(
+            lazy_record.genotype_column(b"AD")?,
+            record.genotype_column(b"AD")?
+        );
+
+        bcf_writer.write_record(record)?;
+        lazy_bcf_writer.write_record(lazy_record)?;
+
+        assert_eq!(lazy_record.genotypes(), record.genotypes());
+        assert!(lazy_record.is_genotype_lazy());
+
+        let mut lazy_record = lazy_record.clone();
+        lazy_record.parse_genotype();
+        assert!(!lazy_record.is_genotype_lazy());
+        assert_eq!(lazy_record.genotype, record.genotype);
+    }
+    drop(bcf_writer);
+    drop(lazy_bcf_writer);
+    assert_eq!(lazy_bcf_data, bcf_data);
+
+    let sample = &vcf_reader.header().samples()[1];
+    let mut record = records[0].clone();
+    let mut lazy_record = lazy_records[0].clone();
+    record.set_genotype_call(sample, &"1|1".parse()?);
+    lazy_record.set_genotype_call(sample, &"1|1".parse()?);
+    assert!(!lazy_record.is_genotype_lazy());
+    assert_eq!(lazy_record.genotype, record.genotype);
+
+    let mut lazy_record = lazy_records[0].clone();
+    lazy_record.genotypes_mut()[0][0] = vec![b"1|1".to_vec()];
+    assert!(!lazy_record.is_genotype_lazy());
+    assert_eq!(
+        lazy_record.genotype(&vcf_reader.header().samples()[0], b"GT"),
+        Some(&vec![b"1|1".to_vec()])
+    );
+
+    // Reusing a lazy record for eager parsing clears unsplit columns
+    let mut vcf_reader = VCFReader::new(BufReader::new(data))?;
+    let mut record = lazy_records[1].clone();
+    vcf_reader.next_record(&mut record)?;
+    assert!(!record.is_genotype_lazy());
+    assert_eq!(record.genotype, records[0].genotype);
+    Ok(())
+}
+
+#[test]
+fn test_sample_subset() -> Result<(), VCFError> {
+    let data = &include_bytes!("../testfiles/1kGP-subset.vcf")[..];
+    let mut vcf_reader = VCFReader::new(BufReader::new(data))?;
+    let records = vcf_reader.records().collect::<Result<Vec<_>, _>>()?;
+    let all_samples = vcf_reader.header().samples().to_vec();
+    let samples = vec![all_samples[3].clone(), all_samples[0].clone()];
+
+    for lazy_genotype in [false, true].iter() {
+        let mut vcf_reader = VCFReader::new(BufReader::new(data))?;
+        vcf_reader.set_lazy_genotype(*lazy_genotype);
+        vcf_reader.set_samples(&samples)?;
+        assert_eq!(vcf_reader.header().samples(), &samples[..]);
+
+        let mut written = Vec::new();
+        let mut vcf_writer = VCFWriter::new(&mut written, vcf_reader.header())?;
+        let subset_records = vcf_reader.records().collect::<Result<Vec<_>, _>>()?;
+        assert_eq!(subset_records.len(), records.len());
+        for (record, subset_record) in records.iter().zip(subset_records.iter()) {
+            for sample in samples.iter() {
+                for key in record.format.iter() {
+                    assert_eq!(
+                        subset_record.genotype(sample, key),
+                        record.genotype(sample, key)
+                    );
+                }
+            }
+            assert_eq!(subset_record.genotype(&all_samples[1], b"GT"), None);
+            vcf_writer.write_record(subset_record)?;
+        }
+
+        let mut vcf_reader = VCFReader::new(BufReader::new(&written[..]))?;
+        assert_eq!(vcf_reader.header().samples(), &samples[..]);
+        let written_records = vcf_reader.records().collect::<Result<Vec<_>, _>>()?;
+        for (record, written_record) in records.iter().zip(written_records.iter()) {
+            assert_eq!(written_record.genotype.len(), 2);
+            assert_eq!(written_record.genotype[0], record.genotype[3]);
+            assert_eq!(written_record.genotype[1], record.genotype[0]);
+        }
+    }
+
+    let mut vcf_reader = VCFReader::new(BufReader::new(data))?;
+    vcf_reader.set_sample_indices(&[1])?;
+    let view = vcf_reader.next_record_view()?.unwrap();
+    assert_eq!(
+        view.genotype(&all_samples[1], b"AD")
+            .unwrap()
+            .collect::<Vec<_>>(),
+        records[0].genotype(&all_samples[1], b"AD").unwrap().clone()
+    );
+    assert!(view.genotype(&all_samples[0], b"AD").is_none());
+    assert_eq!(
+        view.to_record()?.genotype,
+        vec![records[0].genotype[1].clone()]
+    );
+
+    vcf_reader.set_sample_indices(&[])?;
+    let record = vcf_reader.records().next().unwrap()?;
+    assert!(record.format.is_empty());
+    assert!(record.genotype.is_empty());
+    let mut written = Vec::new();
+    record.write_record(&mut written)?;
+    assert_eq!(written.iter().filter(|x| **x == b'\t').count(), 7);
+
+    assert!(matches!(
+        vcf_reader.set_samples(&[b"UNKNOWN"]),
+        Err(VCFError::UnknownSample(_))
+    ));
+    assert!(matches!(
+        vcf_reader.set_sample_indices(&[all_samples.len()]),
+        Err(VCFError::SampleIndexOutOfRange(_))
+    ));
+    Ok(())
+}
+
 #[test]
 fn test_writer() -> Result<(), VCFError> {
     let vcf_bytes = include_bytes!("../testfiles/simple1.vcf");
@@ -48,7 +288,7 @@ fn test_writer() -> Result<(), VCFError> {
     let mut vcf_reader = VCFReader::new(&mut simple_vcf)?;
     let mut vcf_record = vcf_reader.empty_record();
     let mut buffer = Vec::new();
-    let mut vcf_writer = VCFWriter::new(&mut buffer, &vcf_reader.header())?;
+    let mut vcf_writer = VCFWriter::new(&mut buffer, vcf_reader.header())?;
     while vcf_reader.next_record(&mut vcf_record)? {
         vcf_writer.write_record(&vcf_record)?;
     }
@@ -107,3 +347,106 @@ fn usage_test() -> Result<(), VCFError> {
 
     Ok(())
 }
+
+#[test]
+fn test_bgzf_reader() -> Result<(), VCFError> {
+    let mut reader = VCFReader::new(BGZFReader::new(File::open(
+        "./testfiles/1kGP-subset.vcf.gz",
+    )?))?;
+    let mut vcf_record = reader.empty_record();
+    let mut offsets = Vec::new();
+    let mut records = Vec::new();
+    loop {
+        let offset = reader.virtual_offset();
+        if !reader.next_record(&mut vcf_record)? {
+            break;
+        }
+        offsets.push(offset);
+        let mut line = Vec::new();
+        vcf_record.write_record(&mut line)?;
+        records.push(line);
+    }
+    assert_eq!(records.len(), 306);
+
+    for i in [305, 0, 150].iter() {
+        reader.seek(offsets[*i])?;
+        for expected in records[*i..].iter().take(2) {
+            assert!(reader.next_record(&mut vcf_record)?);
+            let mut line = Vec::new();
+            vcf_record.write_record(&mut line)?;
+            assert_eq!(&line, expected);
+        }
+    }
+
+    Ok(())
+}
+
+#[test]
+fn test_bgzf_reader_seek_parse_error() -> Result<(), VCFError> {
+    let mut writer = BGZFWriter::new(Vec::new());
+    writer.write_all(include_bytes!("../testfiles/simple1.vcf"))?;
+    writer.write_all(b"1\tX\t.\tA\tC\n")?;
+    let data = writer.finish()?;
+
+    let mut reader = VCFReader::new(BGZFReader::new(std::io::Cursor::new(&data[..])))?;
+    let mut record = reader.empty_record();
+    let offset = reader.virtual_offset();
+    let line = loop {
+        match reader.next_record(&mut record) {
+            Ok(true) => (),
+            Err(VCFError::RecordParseError(x)) => break x.line,
+            x => panic!("{:?}", x),
+        }
+    };
+    assert!(line.is_some());
+
+    reader.seek(offset)?;
+    let error = loop {
+        match reader.next_record(&mut record) {
+            Ok(true) => (),
+            Err(VCFError::RecordParseError(x)) => break x,
+            x => panic!("{:?}", x),
+        }
+    };
+    assert_eq!(error.line, None);
+    assert_eq!(&error.field[..], b"POS");
+
+    reader.seek(offset)?;
+    let error = loop {
+        match reader.next_record_view() {
+            Ok(Some(_)) => (),
+            Err(VCFError::RecordParseError(x)) => break x,
+            x => panic!("{:?}", x.map(|_| ())),
+        }
+    };
+    assert_eq!(error.line, None);
+
+    reader.seek(offset)?;
+    reader.set_strictness(Strictness::Skip);
+    while reader.next_record(&mut record)? {}
+    let warnings = reader.take_warnings();
+    assert_eq!(warnings.len(), 1);
+    assert_eq!(warnings[0].detail.line, None);
+    Ok(())
+}
+
+#[test]
+fn test_bgzf_writer() -> Result<(), VCFError> {
+    let vcf_bytes = include_bytes!("../testfiles/1kGP-subset.vcf");
+    let mut vcf_reader = VCFReader::new(BufReader::new(&vcf_bytes[..]))?;
+    let mut vcf_record = vcf_reader.empty_record();
+    let mut vcf_writer = VCFWriter::new(BGZFWriter::new(Vec::new()), vcf_reader.header())?;
+    while vcf_reader.next_record(&mut vcf_record)? {
+        vcf_writer.write_record(&vcf_record)?;
+    }
+    let compressed = vcf_writer.finish()?;
+
+    let mut vcf_reader = VCFReader::new(BGZFReader::new(&compressed[..]))?;
+    let mut record_count = 0;
+    while vcf_reader.next_record(&mut vcf_record)? {
+        record_count += 1;
+    }
+    assert_eq!(record_count, 306);
+
+    Ok(())
+}